@@ -0,0 +1,275 @@
+use crate::xstd::Vec;
+use core::ptr::NonNull;
+use std::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
+
+/// An owned, growable byte buffer whose data pointer is aligned to a requested alignment.
+///
+/// Unlike a `Vec<u8>`, the block is allocated, grown and freed with the real layout, so any power-of-two
+/// alignment (page-sized ones included) is guaranteed for the whole life of the buffer.
+/// It is the backing storage of `StructBuffer`.
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+    align: usize,
+}
+
+impl AlignedBuffer {
+    /// Creates an empty buffer aligned to `align`. It does not allocate.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two.
+    pub fn new(align: usize) -> Self {
+        if !align.is_power_of_two() {
+            panic!("Invalid alignment!")
+        }
+
+        AlignedBuffer {
+            ptr: unsafe { NonNull::new_unchecked(align as *mut u8) },
+            len: 0,
+            capacity: 0,
+            align,
+        }
+    }
+
+    /// Creates a buffer of `size` zero bytes aligned to `align`.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two or the size overflows.
+    pub fn zeroed(size: usize, align: usize) -> Self {
+        let mut buffer = Self::new(align);
+        buffer.resize(size, 0);
+        buffer
+    }
+
+    /// Creates a buffer holding a copy of `bytes`, aligned to `align`.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two.
+    pub fn from_slice(bytes: &[u8], align: usize) -> Self {
+        let mut buffer = Self::new(align);
+        buffer.extend_from_slice(bytes);
+        buffer
+    }
+
+    /// The alignment of the data pointer.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Resizes the buffer to `new_len` bytes, filling the new bytes with `value`.
+    ///
+    /// # Panics
+    /// Panics if the size overflows.
+    pub fn resize(&mut self, new_len: usize, value: u8) {
+        if new_len > self.len {
+            self.reserve(new_len - self.len);
+            unsafe { core::ptr::write_bytes(self.ptr.as_ptr().add(self.len), value, new_len - self.len) };
+        }
+        self.len = new_len;
+    }
+
+    /// Appends a copy of `bytes`.
+    ///
+    /// # Panics
+    /// Panics if the size overflows.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), self.ptr.as_ptr().add(self.len), bytes.len()) };
+        self.len += bytes.len();
+    }
+
+    /// Inserts a copy of `bytes` at `at`, shifting the following bytes.
+    ///
+    /// # Panics
+    /// Panics if `at > len()` or the size overflows.
+    pub fn insert_from_slice(&mut self, at: usize, bytes: &[u8]) {
+        if at > self.len {
+            panic!("Insufficient buffer size!")
+        }
+
+        self.reserve(bytes.len());
+        unsafe {
+            let start = self.ptr.as_ptr().add(at);
+            core::ptr::copy(start, start.add(bytes.len()), self.len - at);
+            core::ptr::copy_nonoverlapping(bytes.as_ptr(), start, bytes.len());
+        }
+        self.len += bytes.len();
+    }
+
+    /// Removes the `range` bytes, shifting the following bytes.
+    ///
+    /// # Panics
+    /// Panics if `range` is reversed or out of the buffer bounds.
+    pub fn remove_range(&mut self, range: core::ops::Range<usize>) {
+        if range.start > range.end || range.end > self.len {
+            panic!("Insufficient buffer size!")
+        }
+
+        unsafe {
+            let base = self.ptr.as_ptr();
+            core::ptr::copy(base.add(range.end), base.add(range.start), self.len - range.end);
+        }
+        self.len -= range.end - range.start;
+    }
+
+    /// Reserves capacity for at least `additional` more bytes.
+    ///
+    /// # Panics
+    /// Panics if the size overflows.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("Buffer size overflow!");
+        if required > self.capacity {
+            self.grow(required.max(self.capacity.saturating_mul(2)).max(8));
+        }
+    }
+
+    fn grow(&mut self, capacity: usize) {
+        let layout = Layout::from_size_align(capacity, self.align).expect("Buffer size overflow!");
+        let ptr = unsafe {
+            if self.capacity == 0 {
+                alloc(layout)
+            } else {
+                realloc(self.ptr.as_ptr(), self.layout(), capacity)
+            }
+        };
+
+        self.ptr = NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout));
+        self.capacity = capacity;
+    }
+
+    fn layout(&self) -> Layout {
+        // Validated when the block was allocated.
+        unsafe { Layout::from_size_align_unchecked(self.capacity, self.align) }
+    }
+}
+
+impl core::ops::Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl core::ops::DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Clone for AlignedBuffer {
+    /// Copies the bytes into a new block with the same alignment.
+    fn clone(&self) -> Self {
+        Self::from_slice(self, self.align)
+    }
+}
+
+/// Compares the bytes only, the alignments may differ.
+impl PartialEq for AlignedBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for AlignedBuffer {}
+
+impl core::fmt::Debug for AlignedBuffer {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl From<AlignedBuffer> for Vec<u8> {
+    fn from(buffer: AlignedBuffer) -> Self {
+        buffer.to_vec()
+    }
+}
+
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        if self.capacity != 0 {
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout()) }
+        }
+    }
+}
+
+// The buffer uniquely owns its block, like a `Vec<u8>`.
+unsafe impl Send for AlignedBuffer {}
+unsafe impl Sync for AlignedBuffer {}
+
+/// Allocates an uninitialized buffer of `size` bytes whose data pointer is aligned to `align`.
+///
+/// The alignment is kept when the buffer grows. An empty buffer does not allocate; its pointer is still aligned.
+///
+/// # Safety
+/// The allocated buffer is uninitialized and should be entirely rewritten before read.
+///
+/// # Panics
+/// Panics if `align` is not a power of two or the size overflows.
+pub unsafe fn alloc_buffer_aligned(size: usize, align: usize) -> AlignedBuffer {
+    let mut buffer = AlignedBuffer::new(align);
+    if size != 0 {
+        buffer.grow(size);
+        buffer.len = size;
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_aligned(buffer: &AlignedBuffer, align: usize) -> bool {
+        buffer.as_ptr() as usize % align == 0
+    }
+
+    #[test]
+    fn alloc_buffer_aligned_alignment() {
+        for &align in &[1, 2, 4, 8, 16, 64, 512, 4096] {
+            for &size in &[0, 1, 3, 512, 8192] {
+                let mut buffer = unsafe { alloc_buffer_aligned(size, align) };
+                assert_eq!(size, buffer.len());
+                assert_eq!(align, buffer.align());
+                assert!(is_aligned(&buffer, align));
+
+                buffer.fill(0xAB);
+                buffer.resize(size * 3 + 1, 0);
+                assert!(is_aligned(&buffer, align));
+                assert!(buffer[..size].iter().all(|&byte| byte == 0xAB));
+                assert!(buffer[size..].iter().all(|&byte| byte == 0));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn alloc_buffer_aligned_not_power_of_two() {
+        unsafe { alloc_buffer_aligned(8, 3) };
+    }
+
+    #[test]
+    fn edit_bytes() {
+        let mut buffer = AlignedBuffer::from_slice(b"ad", 64);
+        buffer.insert_from_slice(1, b"bc");
+        buffer.extend_from_slice(b"ef");
+        assert_eq!(b"abcdef", &buffer[..]);
+
+        buffer.remove_range(1..3);
+        assert_eq!(b"adef", &buffer[..]);
+        buffer.resize(2, 0);
+        assert_eq!(AlignedBuffer::from_slice(b"ad", 1), buffer);
+
+        let copy = buffer.clone();
+        assert_eq!((64, &b"ad"[..]), (copy.align(), &copy[..]));
+        assert!(is_aligned(&copy, 64));
+        assert_eq!(vec![b'a', b'd'], Vec::from(copy));
+        assert_eq!(&[0; 3], &AlignedBuffer::zeroed(3, 4096)[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_range_out_of_bounds() {
+        AlignedBuffer::zeroed(4, 8).remove_range(2..5);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::xstd::Vec;
+use crate::{SharedError, StructBuffer};
+
+/// A mutable view of a byte slice as a bitmap, LSB-first within each byte.
+///
+/// Bit `i` lives in `bytes[i / 8]` at position `i % 8`.
+pub struct BitViewMut<'a> {
+    bytes: &'a mut [u8],
+}
+
+impl<'a> BitViewMut<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Number of bits in the view.
+    pub fn len(&self) -> usize {
+        self.bytes.len() * 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.bytes.get(index / 8).map(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    /// Sets or clears the bit at `index`. Returns `false` if `index` is out of range.
+    pub fn set(&mut self, index: usize, value: bool) -> bool {
+        match self.bytes.get_mut(index / 8) {
+            Some(byte) => {
+                if value {
+                    *byte |= 1 << (index % 8);
+                } else {
+                    *byte &= !(1 << (index % 8));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finds the first zero bit, sets it and returns its index, or `None` if every bit is set.
+    ///
+    /// Fully allocated regions are skipped a 64-bit word at a time.
+    pub fn allocate_first_free(&mut self) -> Option<usize> {
+        let mut words = self.bytes.chunks_exact(8);
+        let mut base = 0;
+        let mut found = None;
+        for word in &mut words {
+            let mut value = [0_u8; 8];
+            value.copy_from_slice(word);
+            let value = u64::from_le_bytes(value);
+            if value != u64::MAX {
+                found = Some(base + (!value).trailing_zeros() as usize);
+                break;
+            }
+            base += 64;
+        }
+
+        if found.is_none() {
+            let tail = words.remainder();
+            found = tail
+                .iter()
+                .position(|&byte| byte != 0xFF)
+                .map(|pos| base + pos * 8 + (!tail[pos]).trailing_zeros() as usize);
+        }
+
+        let index = found?;
+        self.set(index, true);
+        Some(index)
+    }
+}
+
+/// Extracts `count` fields of `bit_width` bits (1 to 32) from an MSB-first bit stream.
+///
+/// Returns `None` for an unsupported width or if `bytes` holds fewer than `count * bit_width` bits.
+pub fn read_packed_fields(bytes: &[u8], bit_width: usize, count: usize) -> Option<Vec<u32>> {
+    if bit_width == 0 || bit_width > 32 || count.checked_mul(bit_width)? > bytes.len().checked_mul(8)? {
+        return None;
+    }
+
+    let bit = |pos: usize| u32::from(bytes[pos / 8] >> (7 - pos % 8)) & 1;
+    let field = |index: usize| (index * bit_width..(index + 1) * bit_width).fold(0, |value, pos| value << 1 | bit(pos));
+    Some((0..count).map(field).collect())
+}
+
+/// Packs `values` into `dst` as fields of `bit_width` bits (1 to 32), MSB-first,
+/// the inverse of [`read_packed_fields`]. Returns the number of bytes written.
+///
+/// Bits of the final partial byte that follow the last field are left untouched.
+pub fn write_packed_fields(dst: &mut [u8], bit_width: usize, values: &[u32]) -> Result<usize, SharedError> {
+    if bit_width == 0 || bit_width > 32 {
+        return Err(SharedError::InvalidWidth { width: bit_width });
+    }
+
+    let max = u32::MAX >> (32 - bit_width);
+    if let Some(&value) = values.iter().find(|&&value| value > max) {
+        return Err(SharedError::ValueTooLarge { value: value.into(), max: max.into() });
+    }
+
+    let size = values.len().checked_mul(bit_width).ok_or(SharedError::Overflow)?.div_ceil(8);
+    if size > dst.len() {
+        return Err(SharedError::OutOfBounds { offset: 0, size, len: dst.len() });
+    }
+
+    let mut bits = BitViewMut::new(dst);
+    for (index, &value) in values.iter().enumerate() {
+        for bit in 0..bit_width {
+            // BitViewMut is LSB-first within a byte, the stream is MSB-first
+            let pos = index * bit_width + bit;
+            bits.set(pos / 8 * 8 + (7 - pos % 8), value >> (bit_width - 1 - bit) & 1 != 0);
+        }
+    }
+
+    Ok(size)
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Reads a presence bitmap of `field_count` bits (`ceil(field_count / 8)` bytes) at `offset`.
+    ///
+    /// Bits are LSB-first: field `i` is bit `i % 8` of byte `i / 8`.
+    pub fn read_presence_bitmap(&self, offset: usize, field_count: usize) -> Option<Vec<bool>> {
+        let size = field_count.div_ceil(8);
+        let bytes = self.buffer.get(offset..offset.checked_add(size)?)?;
+        Some((0..field_count).map(|i| bytes[i / 8] & (1 << (i % 8)) != 0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_first_free() {
+        let mut bytes = [0xFF_u8; 10];
+        bytes[9] = 0b1111_0111;
+        let mut bits = BitViewMut::new(&mut bytes);
+        assert_eq!(80, bits.len());
+        assert_eq!(Some(false), bits.get(75));
+
+        assert_eq!(Some(75), bits.allocate_first_free());
+        assert_eq!(Some(true), bits.get(75));
+        assert_eq!(None, bits.allocate_first_free());
+
+        assert!(bits.set(3, false));
+        assert!(!bits.set(80, false));
+        assert_eq!(Some(3), bits.allocate_first_free());
+
+        assert!(bits.set(70, false));
+        assert!(bits.set(12, false));
+        assert_eq!(Some(12), bits.allocate_first_free());
+        assert_eq!(Some(70), bits.allocate_first_free());
+
+        let mut empty: [u8; 0] = [];
+        assert_eq!(None, BitViewMut::new(&mut empty).allocate_first_free());
+    }
+
+    #[test]
+    fn packed_fields() {
+        let bytes = [0b1010_0111, 0b0100_0000];
+        assert_eq!(Some(vec![0b101, 0b001, 0b110]), read_packed_fields(&bytes, 3, 3));
+        assert_eq!(Some(vec![0xA7, 0x40]), read_packed_fields(&bytes, 8, 2));
+        assert_eq!(Some(vec![0xA740]), read_packed_fields(&bytes, 16, 1));
+        assert_eq!(Some(vec![]), read_packed_fields(&bytes, 5, 0));
+        assert_eq!(None, read_packed_fields(&bytes, 3, 6));
+        assert_eq!(None, read_packed_fields(&bytes, 0, 1));
+        assert_eq!(None, read_packed_fields(&bytes, 33, 0));
+
+        let ones = [0xFF; 5];
+        assert_eq!(Some(vec![u32::MAX]), read_packed_fields(&ones, 32, 1));
+    }
+
+    #[test]
+    fn write_packed_fields_round_trip() {
+        let mut dst = [0_u8; 3];
+        assert_eq!(Ok(2), write_packed_fields(&mut dst, 3, &[0b101, 0b001, 0b110, 0b010]));
+        assert_eq!([0b1010_0111, 0b0010_0000, 0], dst);
+        assert_eq!(Some(vec![0b101, 0b001, 0b110, 0b010]), read_packed_fields(&dst, 3, 4));
+
+        let values = [0x1FFF, 0, 0x1234, 0x0ABC];
+        let mut dst = [0xFF_u8; 7];
+        assert_eq!(Ok(7), write_packed_fields(&mut dst, 13, &values));
+        assert_eq!(Some(values.to_vec()), read_packed_fields(&dst, 13, 4));
+        assert_eq!(0x0F, dst[6] & 0x0F);
+
+        assert_eq!(Err(SharedError::ValueTooLarge { value: 8, max: 7 }), write_packed_fields(&mut dst, 3, &[8]));
+        assert_eq!(Err(SharedError::InvalidWidth { width: 0 }), write_packed_fields(&mut dst, 0, &[]));
+        let err = write_packed_fields(&mut dst, 32, &[1, 2]).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 0, size: 8, len: 7 }, err);
+    }
+
+    #[test]
+    fn read_presence_bitmap() {
+        let buffer = StructBuffer::<[u8; 3]>::with_value(&[0xFF, 0b0000_0101, 0b0000_0010]);
+        assert_eq!(Some(vec![true, false, true]), buffer.read_presence_bitmap(1, 3));
+        assert_eq!(
+            Some(vec![true, false, true, false, false, false, false, false, false, true]),
+            buffer.read_presence_bitmap(1, 10)
+        );
+        assert_eq!(Some(vec![]), buffer.read_presence_bitmap(3, 0));
+        assert_eq!(None, buffer.read_presence_bitmap(2, 9));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::records::values_bytes;
+use crate::{Pod, StructBuffer};
+
+/// Builds a `StructBuffer` whose ext size is not known upfront by appending to the ext region.
+///
+/// The backing buffer grows as a `Vec`, so the appends are amortized.
+pub struct StructBufferBuilder<T: Sized + Clone + Copy> {
+    inner: StructBuffer<T>,
+}
+
+impl<T: Sized + Clone + Copy> StructBufferBuilder<T> {
+    /// Starts a buffer holding just `header`.
+    pub fn new(header: T) -> Self {
+        Self {
+            inner: StructBuffer::with_value(&header),
+        }
+    }
+
+    /// Appends `bytes` to the ext region and returns their offset relative to the ext region start.
+    pub fn append(&mut self, bytes: &[u8]) -> usize {
+        let offset = self.inner.ext_size();
+        self.inner.buffer.extend_from_slice(bytes);
+        offset
+    }
+
+    /// Appends the bytes of `value` and returns their ext offset, see [`append`](Self::append).
+    pub fn append_value<U: Pod>(&mut self, value: U) -> usize {
+        self.append(values_bytes(core::slice::from_ref(&value)))
+    }
+
+    /// The ext bytes appended so far.
+    pub fn ext_size(&self) -> usize {
+        self.inner.ext_size()
+    }
+
+    pub fn build(self) -> StructBuffer<T> {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_incrementally() {
+        let mut builder = StructBufferBuilder::new([0xAA_u8, 0xBB]);
+        assert_eq!(0, builder.append(b"name"));
+        assert_eq!(4, builder.append_value(0x0102_u16.to_le()));
+        assert_eq!(6, builder.append(&[]));
+        assert_eq!(6, builder.append_value([7_u8; 3]));
+        assert_eq!(9, builder.ext_size());
+
+        let buffer = builder.build();
+        assert_eq!([0xAA, 0xBB], buffer.copy());
+        assert_eq!(b"name\x02\x01\x07\x07\x07", buffer.ext_buffer());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::endian::read_uint;
+#[cfg(feature = "checksum")]
+use crate::endian::write_u32_le;
+#[cfg(feature = "checksum")]
+use crate::ext::check_range;
+#[cfg(feature = "checksum")]
+use crate::{AsByteSlice, Pod};
+use crate::{SharedError, StructBuffer};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Computes the 64-bit FNV-1a fingerprint of `bytes`.
+///
+/// Fast and deterministic, suitable for deduplicating identical sectors.
+/// It is NOT cryptographically secure: collisions can be crafted trivially.
+pub fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(feature = "checksum")]
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+#[cfg(feature = "checksum")]
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+/// Computes the standard CRC-32 (IEEE 802.3, as used by zip, GPT and VHD) of `bytes`.
+#[cfg(feature = "checksum")]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !crc32_update(!0, bytes.iter().copied())
+}
+
+/// Computes the CRC-32 of the bytes of `value`, e.g. a whole header.
+///
+/// Integer types, their slices, arrays and `Vec`s have no padding; for a `StructBuffer` the result
+/// is only deterministic if all its bytes were initialized (i.e. it was not created with `new`/`with_ext`).
+#[cfg(feature = "checksum")]
+pub fn crc32_of<T: AsByteSlice + ?Sized>(value: &T) -> u32 {
+    crc32(unsafe { value.as_byte_slice() })
+}
+
+#[cfg(feature = "checksum")]
+fn crc32_update(crc: u32, bytes: impl Iterator<Item = u8>) -> u32 {
+    bytes.fold(crc, |crc, byte| CRC32_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// Computes the 16-bit ones-complement Internet checksum (RFC 1071) of `bytes`.
+///
+/// Bytes are summed as big-endian words; an odd final byte is padded with a zero byte.
+pub fn internet_checksum(bytes: &[u8]) -> u16 {
+    let mut words = bytes.chunks_exact(2);
+    let mut sum: u64 = (&mut words).map(|word| u64::from(u16::from_be_bytes([word[0], word[1]]))).sum();
+    if let [last] = words.remainder() {
+        sum += u64::from(*last) << 8;
+    }
+    !fold_ones_complement(sum)
+}
+
+fn fold_ones_complement(mut sum: u64) -> u16 {
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum as u16
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Computes the FNV-1a fingerprint of the whole backing buffer (struct and ext bytes).
+    ///
+    /// Not cryptographically secure, see [`fingerprint`].
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(self.buffer())
+    }
+
+    /// Verifies the little-endian checksum field of `width` bytes (1 to 8) at `checksum_offset`
+    /// against `f` computed over the buffer with that field zeroed.
+    ///
+    /// The computed value is truncated to `width` bytes before the comparison.
+    pub fn verify_with<F: Fn(&[u8]) -> u64>(
+        &self,
+        checksum_offset: usize,
+        width: usize,
+        f: F,
+    ) -> Result<bool, SharedError> {
+        if width == 0 || width > 8 {
+            return Err(SharedError::InvalidWidth { width });
+        }
+
+        let stored = read_uint(self.buffer(), checksum_offset, width, true)
+            .ok_or_else(|| self.out_of_bounds(checksum_offset, width))?;
+
+        let mut bytes = self.buffer().to_vec();
+        bytes[checksum_offset..checksum_offset + width].iter_mut().for_each(|byte| *byte = 0);
+        let mask = u64::MAX >> (64 - width * 8);
+        Ok(f(&bytes) & mask == stored)
+    }
+
+    /// Computes the Internet checksum of the whole buffer, see [`internet_checksum`].
+    pub fn internet_checksum(&self) -> u16 {
+        match self.as_u16_slice() {
+            // The ones-complement sum is byte order independent: summing native words
+            // gives the byte-swapped big-endian sum on little-endian targets.
+            Some(words) => {
+                let sum = words.iter().map(|&word| u64::from(word)).sum();
+                !u16::from_be(fold_ones_complement(sum))
+            }
+            None => internet_checksum(self.buffer()),
+        }
+    }
+}
+
+#[cfg(feature = "checksum")]
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Computes the CRC-32 of the struct bytes and of the ext bytes independently.
+    pub fn split_crc32(&self) -> (u32, u32) {
+        let (head, ext) = self.split();
+        (crc32(head), crc32(ext))
+    }
+
+    /// Computes the CRC-32 of the whole buffer as if the `exclude` range were zero bytes,
+    /// which is how most formats checksum a structure containing its own checksum field.
+    ///
+    /// Fails if `exclude` is reversed or out of the buffer bounds.
+    pub fn crc32_excluding(&self, exclude: core::ops::Range<usize>) -> Result<u32, SharedError> {
+        check_range(&exclude, self.len())?;
+
+        let bytes = self.buffer();
+        let crc = crc32_update(!0, bytes[..exclude.start].iter().copied());
+        let crc = crc32_update(crc, core::iter::repeat(0).take(exclude.len()));
+        Ok(!crc32_update(crc, bytes[exclude.end..].iter().copied()))
+    }
+
+    /// Computes the CRC-32 of the whole buffer with the 4-byte field at `crc_offset` zeroed
+    /// and stores it there as little-endian, the write-side counterpart of [`crc32_excluding`](Self::crc32_excluding).
+    pub fn finalize_with_crc(&mut self, crc_offset: usize) -> Result<(), SharedError>
+    where
+        T: Pod,
+    {
+        let end = crc_offset.checked_add(4).ok_or(SharedError::Overflow)?;
+        let crc = self.crc32_excluding(crc_offset..end)?;
+        write_u32_le(&mut self.buffer, crc_offset, crc).unwrap();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_vectors() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fingerprint(b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fingerprint(b"a"));
+        assert_eq!(0x8594_4171_f739_67e8, fingerprint(b"foobar"));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn crc32_vectors() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0x414F_A339, crc32(b"The quick brown fox jumps over the lazy dog"));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn crc32_of_values() {
+        assert_eq!(crc32(b"123456789"), crc32_of(b"123456789"));
+        assert_eq!(crc32(b"123456789"), crc32_of(&b"123456789"[..]));
+        assert_eq!(crc32(&0x0102_0304_u32.to_ne_bytes()), crc32_of(&0x0102_0304_u32));
+        assert_eq!(crc32(b"abcd"), crc32_of(&StructBuffer::<[u8; 4]>::with_value(b"abcd")));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn crc32_excluding() {
+        let mut buffer = StructBuffer::<[u8; 9]>::with_value(b"123456789");
+        assert_eq!(Ok(0xCBF4_3926), buffer.crc32_excluding(0..0));
+
+        let expected = crc32(b"123\x00\x00\x00789");
+        assert_eq!(Ok(expected), buffer.crc32_excluding(3..6));
+        buffer.raw_mut()[3] = 0xFF;
+        assert_eq!(Ok(expected), buffer.crc32_excluding(3..6));
+        assert_eq!(Ok(crc32(&[0; 9])), buffer.crc32_excluding(0..9));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn crc32_excluding_invalid_range() {
+        let err = StructBuffer::<[u8; 9]>::zeroed().crc32_excluding(5..10);
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 5, size: 5, len: 9 }), err);
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn finalize_with_crc() {
+        let mut buffer = StructBuffer::<[u8; 6]>::with_value(b"ab\xFF\xFF\xFF\xFF");
+        buffer.insert_ext(0, b"cd").unwrap();
+        assert_eq!(Ok(()), buffer.finalize_with_crc(2));
+
+        let crc = crc32(b"ab\x00\x00\x00\x00cd");
+        assert_eq!(&crc.to_le_bytes(), &buffer.buffer()[2..6]);
+        assert_eq!(Ok(crc), buffer.crc32_excluding(2..6));
+
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 5, size: 4, len: 8 }), buffer.finalize_with_crc(5));
+        assert_eq!(Err(SharedError::Overflow), buffer.finalize_with_crc(usize::MAX));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn split_crc32() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(b"1234");
+        assert_eq!((crc32(b"1234"), 0), buffer.split_crc32());
+
+        buffer.insert_ext(0, b"56789").unwrap();
+        assert_eq!((crc32(b"1234"), crc32(b"56789")), buffer.split_crc32());
+    }
+
+    #[test]
+    fn verify_with() {
+        let sum = |bytes: &[u8]| bytes.iter().map(|&b| u64::from(b)).sum::<u64>();
+        let mut buffer = StructBuffer::<[u8; 6]>::with_value(&[0x80, 0x90, 0, 0, 3, 4]);
+        buffer.raw_mut()[2] = 0x17;
+        buffer.raw_mut()[3] = 0x01;
+        assert_eq!(Ok(true), buffer.verify_with(2, 2, sum));
+        assert_eq!(Ok(true), buffer.verify_with(2, 1, |bytes| sum(bytes) + 0xFF));
+        assert_eq!(Ok(false), buffer.verify_with(2, 1, sum));
+
+        assert_eq!(Err(SharedError::InvalidWidth { width: 9 }), buffer.verify_with(0, 9, sum));
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 4, size: 4, len: 6 }), buffer.verify_with(4, 4, sum));
+    }
+
+    #[test]
+    fn internet_checksum_vectors() {
+        // RFC 1071, section 3
+        assert_eq!(0x220D, internet_checksum(&[0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7]));
+        assert_eq!(0xFFFF, internet_checksum(&[]));
+        assert_eq!(!0x0100, internet_checksum(&[0x01]));
+
+        let header: [u8; 20] = [
+            0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xC0, 0xA8, 0x00, 0x01, 0xC0, 0xA8,
+            0x00, 0xC7,
+        ];
+        assert_eq!(0xB861, internet_checksum(&header));
+
+        let mut buffer = StructBuffer::<[u8; 20]>::with_value(&header);
+        assert_eq!(0xB861, buffer.internet_checksum());
+        buffer.insert_ext(0, &[0xAB]).unwrap();
+        assert_eq!(internet_checksum(buffer.buffer()), buffer.internet_checksum());
+    }
+
+    #[test]
+    fn struct_buffer_fingerprint() {
+        let buffer = StructBuffer::<u32>::zeroed();
+        assert_eq!(fingerprint(&[0, 0, 0, 0]), buffer.fingerprint());
+
+        let buffer = StructBuffer::<u32>::with_value(&1);
+        assert_ne!(fingerprint(&[0, 0, 0, 0]), buffer.fingerprint());
+    }
+}
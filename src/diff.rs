@@ -0,0 +1,205 @@
+use crate::endian::read_u32_le;
+use crate::xstd::{BTreeMap, Vec};
+use crate::{Pod, SharedError, StructBuffer};
+use core::convert::TryFrom;
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Returns `offset -> new_byte` for every position where `other` differs from `self`.
+    ///
+    /// If `other` is longer, its whole tail is included. A shorter `other` can not be
+    /// represented by the patch: the extra bytes of `self` are simply ignored.
+    pub fn make_patch(&self, other: &Self) -> BTreeMap<usize, u8> {
+        let old = self.buffer();
+        other
+            .buffer()
+            .iter()
+            .enumerate()
+            .filter(|&(offset, byte)| old.get(offset) != Some(byte))
+            .map(|(offset, &byte)| (offset, byte))
+            .collect()
+    }
+
+    /// Compares the ext regions of `self` (old) and `other` (new) as `U` record tables and
+    /// returns `(index, old, new)` for every changed, added (`old == None`) or removed (`new == None`) record.
+    pub fn record_diff<U: Pod + PartialEq>(&self, other: &Self) -> Vec<(usize, Option<U>, Option<U>)> {
+        let mut old = self.ext_records::<U>();
+        let mut new = other.ext_records::<U>();
+        let mut diff = Vec::new();
+        for index in 0.. {
+            match (old.next(), new.next()) {
+                (None, None) => break,
+                (old, new) if old != new => diff.push((index, old, new)),
+                _ => {}
+            }
+        }
+        diff
+    }
+
+    /// Applies a patch produced by [`make_patch`](Self::make_patch), zero-extending the buffer
+    /// when the patch touches bytes past its end.
+    ///
+    /// Fails with [`SharedError::Overflow`] (leaving the buffer untouched) if the patched length
+    /// exceeds `isize::MAX`, which is only possible for a malformed patch.
+    pub fn apply_patch(&mut self, patch: &BTreeMap<usize, u8>) -> Result<(), SharedError>
+    where
+        T: Pod,
+    {
+        if let Some((&last, _)) = patch.iter().next_back() {
+            let new_len = last.checked_add(1).filter(|&len| len <= isize::MAX as usize).ok_or(SharedError::Overflow)?;
+            if new_len > self.buffer.len() {
+                self.buffer.resize(new_len, 0);
+            }
+        }
+
+        for (&offset, &byte) in patch {
+            self.buffer[offset] = byte;
+        }
+        Ok(())
+    }
+
+    /// Encodes the runs of bytes that changed from `self` to `other` as a compact binary delta.
+    ///
+    /// The delta starts with the new buffer length as a little-endian u32, followed by
+    /// `(offset: u32 LE, length: u32 LE, new_bytes)` records for each changed run, the tail of a longer
+    /// `other` included. Apply it with [`apply_delta`](Self::apply_delta).
+    ///
+    /// # Panics
+    /// Panics if `other` is larger than `u32::MAX` bytes.
+    pub fn binary_delta(&self, other: &Self) -> Vec<u8> {
+        let to_u32 = |value: usize| u32::try_from(value).expect("Delta offset overflow!").to_le_bytes();
+        let (old, new) = (self.buffer(), other.buffer());
+
+        let mut delta = to_u32(new.len()).to_vec();
+        let mut pos = 0;
+        while pos < new.len() {
+            if old.get(pos) == Some(&new[pos]) {
+                pos += 1;
+                continue;
+            }
+
+            let start = pos;
+            while pos < new.len() && old.get(pos) != Some(&new[pos]) {
+                pos += 1;
+            }
+            delta.extend_from_slice(&to_u32(start));
+            delta.extend_from_slice(&to_u32(pos - start));
+            delta.extend_from_slice(&new[start..pos]);
+        }
+        delta
+    }
+
+    /// Applies a delta produced by [`binary_delta`](Self::binary_delta), resizing the buffer to the new length
+    /// (zero-filling when growing).
+    ///
+    /// The whole delta is validated first: on error the buffer is left untouched.
+    pub fn apply_delta(&mut self, delta: &[u8]) -> Result<(), SharedError>
+    where
+        T: Pod,
+    {
+        let truncated = |offset: usize, size: usize| SharedError::OutOfBounds { offset, size, len: delta.len() };
+        let new_len = read_u32_le(delta, 0).ok_or_else(|| truncated(0, 4))? as usize;
+        if new_len < self.struct_size() {
+            return Err(SharedError::ValueOutOfRange {
+                value: new_len as i64,
+                min: self.struct_size() as i64,
+                max: i64::from(u32::MAX),
+            });
+        }
+
+        let mut runs = Vec::new();
+        let mut pos = 4;
+        while pos < delta.len() {
+            let offset = read_u32_le(delta, pos).ok_or_else(|| truncated(pos, 8))? as usize;
+            let len = read_u32_le(delta, pos + 4).ok_or_else(|| truncated(pos, 8))? as usize;
+            let end = (pos + 8).checked_add(len).ok_or(SharedError::Overflow)?;
+            let bytes = delta.get(pos + 8..end).ok_or_else(|| truncated(pos + 8, len))?;
+            if offset.checked_add(len).ok_or(SharedError::Overflow)? > new_len {
+                return Err(SharedError::OutOfBounds { offset, size: len, len: new_len });
+            }
+            runs.push((offset, bytes));
+            pos = end;
+        }
+
+        self.buffer.resize(new_len, 0);
+        for (offset, bytes) in runs {
+            self.buffer[offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn make_and_apply_patch() {
+        let old = StructBuffer::<[u8; 4]>::with_value(&[1, 2, 3, 4]);
+        let mut new = old.clone();
+        new.raw_mut()[1] = 20;
+        new.insert_ext(0, &[5, 6]).unwrap();
+
+        let patch = old.make_patch(&new);
+        let expected: BTreeMap<usize, u8> = [(1, 20), (4, 5), (5, 6)].iter().copied().collect();
+        assert_eq!(expected, patch);
+        assert!(old.make_patch(&old).is_empty());
+
+        let mut replay = old.clone();
+        assert_eq!(Ok(()), replay.apply_patch(&patch));
+        assert_eq!(new.buffer(), replay.buffer());
+    }
+
+    #[test]
+    fn apply_patch_overflow() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(&[1, 2, 3, 4]);
+        for &offset in &[usize::MAX, isize::MAX as usize] {
+            let patch: BTreeMap<usize, u8> = [(0, 9), (offset, 1)].iter().copied().collect();
+            assert_eq!(Err(SharedError::Overflow), buffer.apply_patch(&patch));
+        }
+        assert_eq!(&[1, 2, 3, 4], buffer.buffer());
+    }
+
+    #[test]
+    fn binary_delta_round_trip() {
+        let old = StructBuffer::<[u8; 4]>::with_records_from([1, 2, 3, 4], &[5_u8, 6, 7]);
+        let mut new = old.clone();
+        new.raw_mut()[1] = 20;
+        new.raw_mut()[2] = 30;
+        new.ext_buffer_mut()[2] = 70;
+        new.insert_ext(3, &[8]).unwrap();
+
+        let delta = old.binary_delta(&new);
+        assert_eq!(vec![8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 20, 30, 6, 0, 0, 0, 2, 0, 0, 0, 70, 8], delta);
+        let mut replay = old.clone();
+        assert_eq!(Ok(()), replay.apply_delta(&delta));
+        assert_eq!(new.buffer(), replay.buffer());
+
+        // shrinking back to the old length
+        assert_eq!(Ok(()), replay.apply_delta(&new.binary_delta(&old)));
+        assert_eq!(old.buffer(), replay.buffer());
+        assert_eq!(vec![7, 0, 0, 0], old.binary_delta(&old));
+    }
+
+    #[test]
+    fn apply_delta_malformed() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(&[1, 2, 3, 4]);
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 0, size: 4, len: 2 }), buffer.apply_delta(&[4, 0]));
+
+        let err = buffer.apply_delta(&[4, 0, 0, 0, 3, 0, 0, 0, 2, 0, 0, 0, 9, 9]).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 3, size: 2, len: 4 }, err);
+        let err = buffer.apply_delta(&[4, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 9]).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 12, size: 2, len: 13 }, err);
+        let err = buffer.apply_delta(&[2, 0, 0, 0]).unwrap_err();
+        assert_eq!(SharedError::ValueOutOfRange { value: 2, min: 4, max: i64::from(u32::MAX) }, err);
+        assert_eq!(&[1, 2, 3, 4], buffer.buffer());
+    }
+
+    #[test]
+    fn record_diff() {
+        let old = StructBuffer::<u8>::with_records_from(0, &[1_u16, 2, 3]);
+        let new = StructBuffer::<u8>::with_records_from(0, &[1_u16, 5, 3, 4]);
+        assert_eq!(vec![(1, Some(2), Some(5)), (3, None, Some(4))], old.record_diff::<u16>(&new));
+        assert_eq!(vec![(1, Some(5), Some(2)), (3, Some(4), None)], new.record_diff::<u16>(&old));
+        assert!(old.record_diff::<u16>(&old).is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+/// Byte order of a multi-byte value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+macro_rules! impl_read {
+    ($name:ident, $ty:ty, $from:ident) => {
+        /// Reads the value at `offset`, or returns `None` if it does not fit into `bytes`.
+        pub fn $name(bytes: &[u8], offset: usize) -> Option<$ty> {
+            let end = offset.checked_add(core::mem::size_of::<$ty>())?;
+            let mut value = [0_u8; core::mem::size_of::<$ty>()];
+            value.copy_from_slice(bytes.get(offset..end)?);
+            Some(<$ty>::$from(value))
+        }
+    };
+}
+
+macro_rules! impl_write {
+    ($name:ident, $ty:ty, $to:ident) => {
+        /// Writes `value` at `offset`, or returns `None` (leaving `bytes` untouched) if it does not fit.
+        pub fn $name(bytes: &mut [u8], offset: usize, value: $ty) -> Option<()> {
+            let end = offset.checked_add(core::mem::size_of::<$ty>())?;
+            bytes.get_mut(offset..end)?.copy_from_slice(&value.$to());
+            Some(())
+        }
+    };
+}
+
+impl_read!(read_u16_le, u16, from_le_bytes);
+impl_read!(read_u32_le, u32, from_le_bytes);
+impl_read!(read_u64_le, u64, from_le_bytes);
+impl_read!(read_u16_be, u16, from_be_bytes);
+impl_read!(read_u32_be, u32, from_be_bytes);
+impl_read!(read_u64_be, u64, from_be_bytes);
+
+impl_write!(write_u16_le, u16, to_le_bytes);
+impl_write!(write_u32_le, u32, to_le_bytes);
+impl_write!(write_u64_le, u64, to_le_bytes);
+impl_write!(write_u16_be, u16, to_be_bytes);
+impl_write!(write_u32_be, u32, to_be_bytes);
+impl_write!(write_u64_be, u64, to_be_bytes);
+
+/// Reads an unsigned integer of `width` bytes (1 to 8) at `offset`.
+pub(crate) fn read_uint(bytes: &[u8], offset: usize, width: usize, little_endian: bool) -> Option<u64> {
+    if width == 0 || width > 8 {
+        return None;
+    }
+
+    let bytes = bytes.get(offset..offset.checked_add(width)?)?;
+    let fold = |value: u64, &byte: &u8| (value << 8) | u64::from(byte);
+    Some(if little_endian {
+        bytes.iter().rev().fold(0, fold)
+    } else {
+        bytes.iter().fold(0, fold)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write_fixed() {
+        let mut bytes = [0_u8; 9];
+        assert_eq!(Some(()), write_u16_be(&mut bytes, 0, 0x0102));
+        assert_eq!(Some(()), write_u32_le(&mut bytes, 2, 0x0304_0506));
+        assert_eq!([1, 2, 6, 5, 4, 3, 0, 0, 0], bytes);
+        assert_eq!(Some(0x0201), read_u16_le(&bytes, 0));
+        assert_eq!(Some(0x0605_0403), read_u32_be(&bytes, 2));
+
+        assert_eq!(Some(()), write_u64_be(&mut bytes, 1, 0x1122_3344_5566_7788));
+        assert_eq!(Some(0x8877_6655_4433_2211), read_u64_le(&bytes, 1));
+        assert_eq!(Some(0x1122_3344_5566_7788), read_u64_be(&bytes, 1));
+
+        assert_eq!(None, write_u64_le(&mut bytes, 2, 0));
+        assert_eq!(None, write_u16_le(&mut bytes, usize::MAX, 0));
+        assert_eq!(None, read_u32_le(&bytes, 6));
+        assert_eq!(None, read_u16_be(&bytes, 8));
+        assert_eq!(0x11, bytes[1]);
+    }
+
+    #[test]
+    fn read_uint_widths() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(Some(0x0201), read_uint(&bytes, 0, 2, true));
+        assert_eq!(Some(0x0102), read_uint(&bytes, 0, 2, false));
+        assert_eq!(Some(0x0908_0706_0504_0302), read_uint(&bytes, 1, 8, true));
+        assert_eq!(Some(0x0203_0405_0607_0809), read_uint(&bytes, 1, 8, false));
+        assert_eq!(Some(0x030201), read_uint(&bytes, 0, 3, true));
+        assert_eq!(None, read_uint(&bytes, 0, 9, true));
+        assert_eq!(None, read_uint(&bytes, 0, 0, true));
+        assert_eq!(None, read_uint(&bytes, 8, 2, true));
+    }
+}
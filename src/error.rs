@@ -0,0 +1,88 @@
+use crate::StructBuffer;
+use core::fmt;
+
+/// The error type for the fallible `StructBuffer` operations and helpers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SharedError {
+    /// An access of `size` bytes at `offset` does not fit into the `len` available bytes.
+    OutOfBounds { offset: usize, size: usize, len: usize },
+    /// The range `start..end` is reversed.
+    InvalidRange { start: usize, end: usize },
+    /// An offset or size computation overflowed.
+    Overflow,
+    /// A raw enum discriminant has no matching variant.
+    InvalidEnum { value: u32 },
+    /// A decoded value lies outside the expected `min..=max` range.
+    ValueOutOfRange { value: i64, min: i64, max: i64 },
+    /// A size declared by a header does not match the actual number of bytes.
+    SizeMismatch { declared: usize, actual: usize },
+    /// A field width is not supported by the operation.
+    InvalidWidth { width: usize },
+    /// A format version lies outside the supported `min..=max` range.
+    UnsupportedVersion { found: u32, min: u32, max: u32 },
+    /// A value (or a length) is larger than the field can hold.
+    ValueTooLarge { value: u64, max: u64 },
+    /// A linked chain did not terminate within `max_hops` hops (likely a cycle).
+    ChainTooLong { max_hops: usize },
+    /// A reserved byte that must be zero is not, at `offset`.
+    NonZeroReserved { offset: usize },
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedError::OutOfBounds { offset, size, len } => {
+                write!(f, "{} bytes at offset {} are out of bounds (len {})", size, offset, len)
+            }
+            SharedError::InvalidRange { start, end } => write!(f, "invalid range {}..{}", start, end),
+            SharedError::Overflow => write!(f, "offset or size overflow"),
+            SharedError::InvalidEnum { value } => write!(f, "invalid enum value {:#x}", value),
+            SharedError::ValueOutOfRange { value, min, max } => {
+                write!(f, "value {} is out of range {}..={}", value, min, max)
+            }
+            SharedError::SizeMismatch { declared, actual } => write_size_mismatch(f, *declared, *actual),
+            SharedError::InvalidWidth { width } => write!(f, "unsupported field width {}", width),
+            SharedError::UnsupportedVersion { found, min, max } => {
+                write!(f, "unsupported version {} (supported {}..={})", found, min, max)
+            }
+            SharedError::ValueTooLarge { value, max } => write!(f, "value {} exceeds the maximum {}", value, max),
+            SharedError::ChainTooLong { max_hops } => write!(f, "chain exceeds {} hops", max_hops),
+            SharedError::NonZeroReserved { offset } => write!(f, "reserved byte at offset {} is not zero", offset),
+        }
+    }
+}
+
+/// The supplied buffer is smaller than the struct it should hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsufficientBufferError {
+    /// `size_of::<T>()`
+    pub expected: usize,
+    /// The supplied buffer length.
+    pub actual: usize,
+}
+
+impl fmt::Display for InsufficientBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "insufficient buffer size: {} bytes needed, {} supplied", self.expected, self.actual)
+    }
+}
+
+fn write_size_mismatch(f: &mut fmt::Formatter<'_>, declared: usize, actual: usize) -> fmt::Result {
+    if declared > actual {
+        write!(f, "declared size {} but only {} bytes present ({} missing)", declared, actual, declared - actual)
+    } else {
+        write!(f, "declared size {} but {} bytes present ({} extra)", declared, actual, actual - declared)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SharedError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InsufficientBufferError {}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    pub(crate) fn out_of_bounds(&self, offset: usize, size: usize) -> SharedError {
+        SharedError::OutOfBounds { offset, size, len: self.len() }
+    }
+}
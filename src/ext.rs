@@ -0,0 +1,153 @@
+use crate::geometry::padding_to_align;
+use crate::{SharedError, StructBuffer};
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Inserts `bytes` into the ext region at `offset` (relative to the ext region start),
+    /// shifting the following ext bytes right. The struct bytes are left untouched.
+    pub fn insert_ext(&mut self, offset: usize, bytes: &[u8]) -> Result<(), SharedError> {
+        let ext_len = self.ext_size();
+        if offset > ext_len {
+            return Err(SharedError::OutOfBounds { offset, size: 0, len: ext_len });
+        }
+
+        let at = self.struct_size() + offset;
+        self.buffer.insert_from_slice(at, bytes);
+        Ok(())
+    }
+
+    /// Removes the `range` (relative to the ext region start) from the ext region,
+    /// shifting the following ext bytes left. The struct bytes are left untouched.
+    pub fn remove_ext(&mut self, range: core::ops::Range<usize>) -> Result<(), SharedError> {
+        check_range(&range, self.ext_size())?;
+
+        let struct_size = self.struct_size();
+        self.buffer.remove_range(struct_size + range.start..struct_size + range.end);
+        Ok(())
+    }
+
+    /// Resizes the ext region to `new_ext_size` bytes, zero-filling when growing and truncating when shrinking.
+    ///
+    /// The struct bytes are left untouched; a reallocation moves them together with the ext bytes,
+    /// so `raw()` keeps returning the same header value.
+    ///
+    /// # Panics
+    /// Panics if the new buffer size overflows.
+    pub fn resize_ext(&mut self, new_ext_size: usize) {
+        let new_len = self.struct_size().checked_add(new_ext_size).expect("Buffer size overflow!");
+        self.buffer.resize(new_len, 0);
+    }
+
+    /// Appends `pad` bytes to the ext region until the whole buffer length is a multiple of `align`.
+    ///
+    /// `align` must be a power of two.
+    pub fn pad_to_align(&mut self, align: usize, pad: u8) {
+        let new_len = self.len() + padding_to_align(self.len(), align);
+        self.buffer.resize(new_len, pad);
+    }
+
+    /// Returns `len()` rounded up to a multiple of `sector_size`.
+    pub fn sector_aligned_len(&self, sector_size: usize) -> usize {
+        debug_assert!(sector_size != 0, "sector_size must be nonzero");
+        match self.len() % sector_size {
+            0 => self.len(),
+            rem => self.len() + (sector_size - rem),
+        }
+    }
+
+    /// Appends `pad` bytes to the ext region until the buffer length is a multiple of `sector_size`.
+    pub fn pad_to_sector(&mut self, sector_size: usize, pad: u8) {
+        let new_len = self.sector_aligned_len(sector_size);
+        self.buffer.resize(new_len, pad);
+    }
+}
+
+pub(crate) fn check_range(range: &core::ops::Range<usize>, len: usize) -> Result<(), SharedError> {
+    if range.start > range.end {
+        return Err(SharedError::InvalidRange { start: range.start, end: range.end });
+    }
+
+    if range.end > len {
+        return Err(SharedError::OutOfBounds { offset: range.start, size: range.end - range.start, len });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_ext() {
+        let mut buffer = StructBuffer::<u16>::with_value(&0x1234);
+        buffer.insert_ext(0, &[1, 4]).unwrap();
+        buffer.insert_ext(1, &[2, 3]).unwrap();
+        buffer.insert_ext(4, &[5]).unwrap();
+        assert_eq!(&[1, 2, 3, 4, 5], buffer.ext_buffer());
+        assert_eq!(0x1234, buffer.copy());
+
+        let err = buffer.insert_ext(6, &[6]).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 6, size: 0, len: 5 }, err);
+        assert_eq!(7, buffer.len());
+    }
+
+    #[test]
+    fn remove_ext() {
+        let mut buffer = StructBuffer::<u16>::with_value(&0x1234);
+        buffer.insert_ext(0, &[1, 2, 3, 4, 5]).unwrap();
+
+        buffer.remove_ext(1..3).unwrap();
+        assert_eq!(&[1, 4, 5], buffer.ext_buffer());
+        buffer.remove_ext(3..3).unwrap();
+        assert_eq!(&[1, 4, 5], buffer.ext_buffer());
+        assert_eq!(0x1234, buffer.copy());
+
+        let err = buffer.remove_ext(2..4).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 2, size: 2, len: 3 }, err);
+        #[allow(clippy::reversed_empty_ranges)]
+        let err = buffer.remove_ext(2..1).unwrap_err();
+        assert_eq!(SharedError::InvalidRange { start: 2, end: 1 }, err);
+
+        buffer.remove_ext(0..3).unwrap();
+        assert!(!buffer.has_ext_buffer());
+    }
+
+    #[test]
+    fn resize_ext() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(b"HEAD");
+        buffer.insert_ext(0, &[1, 2, 3, 4]).unwrap();
+
+        buffer.resize_ext(8);
+        assert_eq!(*b"HEAD", buffer.copy());
+        assert_eq!(&[1, 2, 3, 4, 0, 0, 0, 0], buffer.ext_buffer());
+
+        buffer.resize_ext(2);
+        assert_eq!(*b"HEAD", buffer.copy());
+        assert_eq!(&[1, 2], buffer.ext_buffer());
+
+        buffer.resize_ext(0);
+        assert_eq!(b"HEAD", buffer.buffer());
+    }
+
+    #[test]
+    fn pad_to_align() {
+        let mut buffer = StructBuffer::<[u8; 3]>::with_value(&[1, 2, 3]);
+        buffer.pad_to_align(8, 0xFF);
+        assert_eq!(&[1, 2, 3, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], buffer.buffer());
+
+        buffer.pad_to_align(4, 0);
+        assert_eq!(8, buffer.len());
+    }
+
+    #[test]
+    fn pad_to_sector() {
+        let mut buffer = StructBuffer::<[u8; 3]>::with_value(&[1, 2, 3]);
+        assert_eq!(512, buffer.sector_aligned_len(512));
+        assert_eq!(520, buffer.sector_aligned_len(520));
+
+        buffer.pad_to_sector(512, 0);
+        assert_eq!(512, buffer.len());
+        assert_eq!(512, buffer.sector_aligned_len(512));
+        assert_eq!(&[1, 2, 3, 0], &buffer.buffer()[..4]);
+    }
+}
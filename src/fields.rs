@@ -0,0 +1,695 @@
+use crate::endian::{read_u32_le, read_u64_le, read_uint, Endianness};
+use crate::ext::check_range;
+use crate::records::ref_at;
+use crate::xstd::Vec;
+use core::convert::TryFrom;
+use crate::{Pod, SharedError, StructBuffer};
+
+/// Converts a raw discriminant into `E`, reporting the raw value if it has no matching variant.
+pub fn try_read_enum<E: TryFrom<u32>>(value: u32) -> Result<E, SharedError> {
+    E::try_from(value).map_err(|_| SharedError::InvalidEnum { value })
+}
+
+/// Reads a signed little-endian 16.16 fixed-point value (16 integer bits, 16 fraction bits) at `offset`.
+///
+/// E.g. `0x0001_8000` is `1.5`. Returns `None` if the four bytes do not fit.
+pub fn read_fixed_16_16(bytes: &[u8], offset: usize) -> Option<f64> {
+    let raw = read_u32_le(bytes, offset)? as i32;
+    Some(f64::from(raw) / 65536.0)
+}
+
+/// Reads a signed fixed-point value with `int_bits` integer and `frac_bits` fraction bits at `offset`,
+/// e.g. 8.8, 16.16 or 2.30.
+///
+/// Returns `None` if `int_bits + frac_bits` is not a whole number of bytes (8 to 64 bits)
+/// or the field does not fit.
+pub fn read_fixed_point(
+    bytes: &[u8],
+    offset: usize,
+    int_bits: usize,
+    frac_bits: usize,
+    little_endian: bool,
+) -> Option<f64> {
+    let bits = int_bits.checked_add(frac_bits)?;
+    if bits == 0 || bits > 64 || bits % 8 != 0 {
+        return None;
+    }
+
+    let raw = read_uint(bytes, offset, bits / 8, little_endian)?;
+    // sign-extend from `bits` to 64 bits
+    let value = ((raw << (64 - bits)) as i64) >> (64 - bits);
+    Some(value as f64 / (1_u128 << frac_bits) as f64)
+}
+
+/// Decodes the two FAT12 12-bit entries packed into the three bytes at `offset`.
+///
+/// Returns `None` if the three bytes do not fit.
+pub fn read_fat12_pair(bytes: &[u8], offset: usize) -> Option<(u16, u16)> {
+    let packed = bytes.get(offset..offset.checked_add(3)?)?;
+    let (b0, b1, b2) = (u16::from(packed[0]), u16::from(packed[1]), u16::from(packed[2]));
+    Some((b0 | (b1 & 0x0F) << 8, b1 >> 4 | b2 << 4))
+}
+
+/// Packs two FAT12 12-bit entries into the three bytes at `offset`, the inverse of [`read_fat12_pair`].
+///
+/// Returns `None` (leaving the bytes untouched) if the three bytes do not fit
+/// or an entry does not fit in 12 bits.
+pub fn write_fat12_pair(bytes: &mut [u8], offset: usize, pair: (u16, u16)) -> Option<()> {
+    if pair.0 > 0xFFF || pair.1 > 0xFFF {
+        return None;
+    }
+
+    let packed = bytes.get_mut(offset..offset.checked_add(3)?)?;
+    packed[0] = pair.0 as u8;
+    packed[1] = (pair.0 >> 8) as u8 | ((pair.1 & 0x0F) << 4) as u8;
+    packed[2] = (pair.1 >> 4) as u8;
+    Some(())
+}
+
+/// Decodes a zigzag-encoded LEB128 varint (protobuf `sint64`) from the start of `bytes`
+/// and returns the value with the number of bytes consumed.
+///
+/// Returns `None` if the varint is truncated, longer than 10 bytes, overflows 64 bits
+/// or ends with a redundant zero byte.
+pub fn read_zigzag_i64(bytes: &[u8]) -> Option<(i64, usize)> {
+    let mut raw = 0_u64;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        if i == 9 && byte > 1 {
+            return None;
+        }
+        raw |= u64::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i != 0 {
+                return None;
+            }
+            return Some(((raw >> 1) as i64 ^ -((raw & 1) as i64), i + 1));
+        }
+    }
+
+    None
+}
+
+/// Appends `value` as a zigzag-encoded LEB128 varint to `out`, the inverse of [`read_zigzag_i64`].
+///
+/// Returns the number of bytes written (1 to 10).
+pub fn push_zigzag_i64(out: &mut Vec<u8>, value: i64) -> usize {
+    let mut raw = ((value << 1) ^ (value >> 63)) as u64;
+    let start = out.len();
+    while raw >= 0x80 {
+        out.push(raw as u8 | 0x80);
+        raw >>= 7;
+    }
+    out.push(raw as u8);
+    out.len() - start
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Reads a directory of `count` little-endian `(offset: u32, length: u32)` pairs at `dir_offset`.
+    ///
+    /// Every entry is validated to point within the buffer.
+    pub fn read_directory(&self, dir_offset: usize, count: usize) -> Result<Vec<(u32, u32)>, SharedError> {
+        let len = self.len();
+        let size = count.checked_mul(8).ok_or(SharedError::Overflow)?;
+        if dir_offset.checked_add(size).ok_or(SharedError::Overflow)? > len {
+            return Err(SharedError::OutOfBounds { offset: dir_offset, size, len });
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for index in 0..count {
+            let pos = dir_offset + index * 8;
+            let entry = (read_u32_le(&self.buffer, pos).unwrap(), read_u32_le(&self.buffer, pos + 4).unwrap());
+            if self.resolve_entry(entry).is_none() {
+                return Err(SharedError::OutOfBounds { offset: entry.0 as usize, size: entry.1 as usize, len });
+            }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the bytes referenced by a directory `(offset, length)` entry, or `None` if it is out of bounds.
+    pub fn resolve_entry(&self, entry: (u32, u32)) -> Option<&[u8]> {
+        let start = entry.0 as usize;
+        let end = start.checked_add(entry.1 as usize)?;
+        self.buffer.get(start..end)
+    }
+
+    /// Reads a little-endian u32 discriminant at `offset` and converts it into `E`.
+    pub fn read_enum<E: TryFrom<u32>>(&self, offset: usize) -> Result<E, SharedError> {
+        let value = read_u32_le(&self.buffer, offset).ok_or_else(|| self.out_of_bounds(offset, 4))?;
+        try_read_enum(value)
+    }
+
+    /// Borrows the bytes at `offset` as both `A` and `B`, modelling an on-disk union.
+    ///
+    /// Returns `None` unless both interpretations fit in the buffer and are properly aligned.
+    pub fn read_union<A: Pod, B: Pod>(&self, offset: usize) -> Option<(&A, &B)> {
+        Some((ref_at::<A>(&self.buffer, offset)?, ref_at::<B>(&self.buffer, offset)?))
+    }
+
+    /// Reads a little-endian i32 at `offset` and checks that it lies within `range`.
+    pub fn read_i32_checked(&self, offset: usize, range: core::ops::RangeInclusive<i32>) -> Result<i32, SharedError> {
+        let value = read_u32_le(&self.buffer, offset).ok_or_else(|| self.out_of_bounds(offset, 4))? as i32;
+        if !range.contains(&value) {
+            return Err(SharedError::ValueOutOfRange {
+                value: value.into(),
+                min: (*range.start()).into(),
+                max: (*range.end()).into(),
+            });
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the names of all `(mask, name)` pairs whose mask bits are all set in `value`.
+    ///
+    /// Set bits without a mapping are not reported.
+    pub fn flag_names(&self, value: u32, names: &[(u32, &'static str)]) -> Vec<&'static str> {
+        names.iter().filter(|&&(mask, _)| mask != 0 && value & mask == mask).map(|&(_, name)| name).collect()
+    }
+
+    /// Reads the 16 raw bytes of a GUID at `offset`, in on-disk (mixed-endian) byte order.
+    pub fn read_guid(&self, offset: usize) -> Option<[u8; 16]> {
+        let mut guid = [0_u8; 16];
+        guid.copy_from_slice(self.buffer.get(offset..offset.checked_add(16)?)?);
+        Some(guid)
+    }
+
+    /// Reads a GUID at `offset` and returns the name of the matching `known` entry.
+    ///
+    /// `known` GUIDs are compared as raw on-disk bytes, as returned by [`read_guid`](Self::read_guid).
+    pub fn read_guid_type(&self, offset: usize, known: &[([u8; 16], &'static str)]) -> Option<&'static str> {
+        let guid = self.read_guid(offset)?;
+        known.iter().find(|(known, _)| *known == guid).map(|&(_, name)| name)
+    }
+
+    /// Reads `count` little-endian `(start_block: u64, block_count: u64)` extents at `offset`
+    /// and converts them to absolute byte ranges using `block_size`.
+    pub fn read_extents(
+        &self,
+        offset: usize,
+        count: usize,
+        block_size: usize,
+    ) -> Result<Vec<core::ops::Range<u64>>, SharedError> {
+        let size = count.checked_mul(16).ok_or(SharedError::Overflow)?;
+        if offset.checked_add(size).ok_or(SharedError::Overflow)? > self.len() {
+            return Err(self.out_of_bounds(offset, size));
+        }
+
+        let block_size = block_size as u64;
+        (0..count)
+            .map(|index| {
+                let pos = offset + index * 16;
+                let start_block = read_u64_le(&self.buffer, pos).unwrap();
+                let block_count = read_u64_le(&self.buffer, pos + 8).unwrap();
+                let start = start_block.checked_mul(block_size).ok_or(SharedError::Overflow)?;
+                let len = block_count.checked_mul(block_size).ok_or(SharedError::Overflow)?;
+                Ok(start..start.checked_add(len).ok_or(SharedError::Overflow)?)
+            })
+            .collect()
+    }
+
+    /// Sums `count` little-endian `(run_length: u32, is_present: u32)` entries of a sparse map at `map_offset`
+    /// and returns the logical (virtual) size, i.e. the total run length times `unit`.
+    ///
+    /// Absent runs count towards the logical size as well.
+    pub fn sparse_logical_size(&self, map_offset: usize, count: usize, unit: u64) -> Result<u64, SharedError> {
+        let size = count.checked_mul(8).ok_or(SharedError::Overflow)?;
+        if map_offset.checked_add(size).ok_or(SharedError::Overflow)? > self.len() {
+            return Err(self.out_of_bounds(map_offset, size));
+        }
+
+        (0..count).try_fold(0_u64, |total, index| {
+            let run_length = u64::from(read_u32_le(&self.buffer, map_offset + index * 8).unwrap());
+            let run_size = run_length.checked_mul(unit).ok_or(SharedError::Overflow)?;
+            total.checked_add(run_size).ok_or(SharedError::Overflow)
+        })
+    }
+
+    /// Reads a u32 count of seconds since a custom epoch at `offset` and converts it to Unix time
+    /// by adding `epoch_offset_secs` (the Unix time of that epoch).
+    ///
+    /// Use [`read_epoch_time64`](Self::read_epoch_time64) for 64-bit fields.
+    /// Returns `None` if the field does not fit or the result overflows.
+    pub fn read_epoch_time(&self, offset: usize, epoch_offset_secs: i64, little_endian: bool) -> Option<i64> {
+        let secs = read_uint(&self.buffer, offset, 4, little_endian)? as i64;
+        secs.checked_add(epoch_offset_secs)
+    }
+
+    /// The same as [`read_epoch_time`](Self::read_epoch_time), but for a u64 seconds field.
+    pub fn read_epoch_time64(&self, offset: usize, epoch_offset_secs: i64, little_endian: bool) -> Option<i64> {
+        let secs = read_uint(&self.buffer, offset, 8, little_endian)?;
+        i64::try_from(secs).ok()?.checked_add(epoch_offset_secs)
+    }
+
+    /// Reads a u32 self-relative pointer at `field_offset` and returns the absolute target offset
+    /// `field_offset + value`, or `None` if the field or the target is outside the buffer.
+    pub fn read_self_relative(&self, field_offset: usize, little_endian: bool) -> Option<usize> {
+        let value = read_uint(&self.buffer, field_offset, 4, little_endian)?;
+        let target = field_offset.checked_add(usize::try_from(value).ok()?)?;
+        if target < self.len() {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+    /// Reads a u32 field at `offset` that uses `none_sentinel` (e.g. `0xFFFF_FFFF`) to mean "absent".
+    ///
+    /// The outer `Option` is `None` if the field does not fit, the inner one is `None` if the field
+    /// holds the sentinel.
+    pub fn read_optional_u32(&self, offset: usize, none_sentinel: u32, little_endian: bool) -> Option<Option<u32>> {
+        let value = read_uint(&self.buffer, offset, 4, little_endian)? as u32;
+        Some(Some(value).filter(|&value| value != none_sentinel))
+    }
+
+    /// The u16 variant of [`read_optional_u32`](Self::read_optional_u32).
+    pub fn read_optional_u16(&self, offset: usize, none_sentinel: u16, little_endian: bool) -> Option<Option<u16>> {
+        let value = read_uint(&self.buffer, offset, 2, little_endian)? as u16;
+        Some(Some(value).filter(|&value| value != none_sentinel))
+    }
+
+    /// The u64 variant of [`read_optional_u32`](Self::read_optional_u32).
+    pub fn read_optional_u64(&self, offset: usize, none_sentinel: u64, little_endian: bool) -> Option<Option<u64>> {
+        let value = read_uint(&self.buffer, offset, 8, little_endian)?;
+        Some(Some(value).filter(|&value| value != none_sentinel))
+    }
+
+    /// Follows a linked chain from `head` using `next_fn` (returning `None` at the end of the chain)
+    /// and returns the number of nodes, `head` included.
+    ///
+    /// Fails with [`SharedError::ChainTooLong`] after `max_hops` links, which guards against cycles
+    /// in untrusted data. `next_fn` is expected to validate the node offsets itself.
+    pub fn chain_length(
+        &self,
+        head: usize,
+        next_fn: impl Fn(usize) -> Option<usize>,
+        max_hops: usize,
+    ) -> Result<usize, SharedError> {
+        let mut node = head;
+        for hops in 0..=max_hops {
+            match next_fn(node) {
+                Some(next) => node = next,
+                None => return Ok(hops + 1),
+            }
+        }
+
+        Err(SharedError::ChainTooLong { max_hops })
+    }
+
+    /// Reads the unsigned `(offset, width, endianness)` fields of `schema`, each with its own byte order.
+    ///
+    /// Fails on the first field whose width is not in `1..=8` or that is out of the buffer bounds.
+    pub fn decode_mixed(&self, schema: &[(usize, usize, Endianness)]) -> Result<Vec<u64>, SharedError> {
+        schema
+            .iter()
+            .map(|&(offset, width, endianness)| {
+                if width == 0 || width > 8 {
+                    return Err(SharedError::InvalidWidth { width });
+                }
+                read_uint(&self.buffer, offset, width, endianness == Endianness::Little)
+                    .ok_or_else(|| self.out_of_bounds(offset, width))
+            })
+            .collect()
+    }
+
+    /// Reads a u64 at `offset` and multiplies it by `scale` (e.g. sectors to bytes).
+    ///
+    /// Returns `None` if the field does not fit or if the multiplication overflows;
+    /// the result never wraps around.
+    pub fn read_u64_scaled(&self, offset: usize, scale: u64, little_endian: bool) -> Option<u64> {
+        read_uint(&self.buffer, offset, 8, little_endian)?.checked_mul(scale)
+    }
+
+    /// Reads a u16 log2 exponent at `offset` and returns the size `1 << exponent` (e.g. a superblock block size).
+    ///
+    /// Returns `None` if the field does not fit or the exponent is 64 or more.
+    pub fn read_shift_size(&self, offset: usize, little_endian: bool) -> Option<u64> {
+        let shift = read_uint(&self.buffer, offset, 2, little_endian)?;
+        1_u64.checked_shl(u32::try_from(shift).ok()?)
+    }
+
+    /// Reads a little-endian u32 format version at `offset` and checks it against `supported`.
+    pub fn check_version(&self, offset: usize, supported: core::ops::RangeInclusive<u32>) -> Result<u32, SharedError> {
+        let found = read_u32_le(&self.buffer, offset).ok_or_else(|| self.out_of_bounds(offset, 4))?;
+        if !supported.contains(&found) {
+            return Err(SharedError::UnsupportedVersion { found, min: *supported.start(), max: *supported.end() });
+        }
+
+        Ok(found)
+    }
+
+    /// Checks that a size `declared` by the header matches `len()`, catching truncated reads.
+    pub fn check_declared_size(&self, declared: usize) -> Result<(), SharedError> {
+        if declared != self.len() {
+            return Err(SharedError::SizeMismatch { declared, actual: self.len() });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every byte of the reserved `ranges` is zero, reporting the first non-zero offset.
+    pub fn check_reserved(&self, ranges: &[core::ops::Range<usize>]) -> Result<(), SharedError> {
+        for range in ranges {
+            check_range(range, self.len())?;
+            if let Some(pos) = self.buffer[range.clone()].iter().position(|&byte| byte != 0) {
+                return Err(SharedError::NonZeroReserved { offset: range.start + pos });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a signed little-endian 16.16 fixed-point value at `offset`, see [`read_fixed_16_16`].
+    pub fn read_fixed_16_16(&self, offset: usize) -> Option<f64> {
+        read_fixed_16_16(&self.buffer, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        File,
+        Dir,
+    }
+
+    impl TryFrom<u32> for Kind {
+        type Error = ();
+
+        fn try_from(value: u32) -> Result<Self, Self::Error> {
+            match value {
+                1 => Ok(Kind::File),
+                2 => Ok(Kind::Dir),
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn read_enum() {
+        assert_eq!(Ok(Kind::Dir), try_read_enum::<Kind>(2));
+        assert_eq!(Err(SharedError::InvalidEnum { value: 7 }), try_read_enum::<Kind>(7));
+
+        let buffer = StructBuffer::<[u8; 6]>::with_value(&[0, 0, 1, 0, 0, 0]);
+        assert_eq!(Ok(Kind::File), buffer.read_enum::<Kind>(2));
+        assert_eq!(Err(SharedError::InvalidEnum { value: 0x100 }), buffer.read_enum::<Kind>(1));
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 3, size: 4, len: 6 }), buffer.read_enum::<Kind>(3));
+    }
+
+    #[test]
+    fn read_union() {
+        let buffer = StructBuffer::<[u8; 4]>::with_value(&[1, 2, 3, 4]);
+        let (word, bytes) = buffer.read_union::<u16, [u8; 2]>(2).unwrap();
+        assert_eq!(u16::from_ne_bytes([3, 4]), *word);
+        assert_eq!([3, 4], *bytes);
+
+        assert!(buffer.read_union::<u16, [u8; 3]>(2).is_none());
+        assert!(buffer.read_union::<u16, u8>(1).is_none());
+        assert!(buffer.read_union::<u8, [u8; 2]>(1).is_some());
+    }
+
+    #[test]
+    fn read_i32_checked() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[0xFF, 0xFF, 0xFF, 0xFF, 10, 0, 0, 0]);
+        assert_eq!(Ok(10), buffer.read_i32_checked(4, 1..=10));
+        assert_eq!(Ok(-1), buffer.read_i32_checked(0, -1..=1));
+        let err = buffer.read_i32_checked(0, 0..=i32::MAX).unwrap_err();
+        assert_eq!(SharedError::ValueOutOfRange { value: -1, min: 0, max: i32::MAX as i64 }, err);
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 6, size: 4, len: 8 }), buffer.read_i32_checked(6, 0..=1));
+    }
+
+    #[test]
+    fn fixed_point() {
+        let bytes = [0x00, 0x80, 0x01, 0x00];
+        assert_eq!(read_fixed_16_16(&bytes, 0), read_fixed_point(&bytes, 0, 16, 16, true));
+        assert_eq!(Some(-0.5), read_fixed_point(&[0xFF, 0x80], 0, 8, 8, false));
+        assert_eq!(Some(1.0), read_fixed_point(&[0x40, 0, 0, 0], 0, 2, 30, false));
+        assert_eq!(Some(-2.0), read_fixed_point(&[0x80, 0, 0, 0], 0, 2, 30, false));
+        assert_eq!(Some(-1.0), read_fixed_point(&[0xFF; 8], 0, 64, 0, true));
+        assert_eq!(Some(0.25), read_fixed_point(&[0, 0x40], 1, 0, 8, true));
+
+        assert_eq!(None, read_fixed_point(&bytes, 0, 4, 8, true));
+        assert_eq!(None, read_fixed_point(&bytes, 0, 0, 0, true));
+        assert_eq!(None, read_fixed_point(&[0; 9], 0, 40, 32, true));
+        assert_eq!(None, read_fixed_point(&bytes, 1, 16, 16, true));
+        assert_eq!(None, read_fixed_point(&bytes, 0, usize::MAX, 1, true));
+    }
+
+    #[test]
+    fn fixed_16_16() {
+        assert_eq!(Some(1.5), read_fixed_16_16(&[0x00, 0x80, 0x01, 0x00], 0));
+        assert_eq!(Some(-1.0), read_fixed_16_16(&[0x00, 0x00, 0xFF, 0xFF], 0));
+        assert_eq!(None, read_fixed_16_16(&[0x00, 0x80, 0x01], 0));
+
+        let buffer = StructBuffer::<[u8; 5]>::with_value(&[0, 0x00, 0x40, 0x02, 0x00]);
+        assert_eq!(Some(2.25), buffer.read_fixed_16_16(1));
+        assert_eq!(None, buffer.read_fixed_16_16(2));
+    }
+
+    #[test]
+    fn flag_names() {
+        const NAMES: &[(u32, &str)] = &[(0x1, "read-only"), (0x2, "hidden"), (0x4, "system"), (0x6, "protected")];
+        let buffer = StructBuffer::<u8>::zeroed();
+        assert_eq!(vec!["read-only"], buffer.flag_names(0x11, NAMES));
+        assert_eq!(vec!["hidden", "system", "protected"], buffer.flag_names(0x6, NAMES));
+        assert!(buffer.flag_names(0, NAMES).is_empty());
+    }
+
+    #[test]
+    fn read_guid_type() {
+        // C12A7328-F81F-11D2-BA4B-00A0C93EC93B
+        const EFI_SYSTEM: [u8; 16] = [
+            0x28, 0x73, 0x2A, 0xC1, 0x1F, 0xF8, 0xD2, 0x11, 0xBA, 0x4B, 0x00, 0xA0, 0xC9, 0x3E, 0xC9, 0x3B,
+        ];
+        const KNOWN: &[([u8; 16], &str)] = &[([0; 16], "Unused"), (EFI_SYSTEM, "EFI System")];
+
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &EFI_SYSTEM).unwrap();
+        assert_eq!(Some(EFI_SYSTEM), buffer.read_guid(1));
+        assert_eq!(Some("EFI System"), buffer.read_guid_type(1, KNOWN));
+        assert_eq!(None, buffer.read_guid_type(0, KNOWN));
+        assert_eq!(None, buffer.read_guid_type(2, KNOWN));
+    }
+
+    #[test]
+    fn read_extents() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &[2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        buffer.insert_ext(16, &[10, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+
+        assert_eq!(Ok(vec![1024..2560, 5120..5632]), buffer.read_extents(1, 2, 512));
+        assert_eq!(Ok(vec![]), buffer.read_extents(1, 0, 512));
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 1, size: 48, len: 33 }), buffer.read_extents(1, 3, 512));
+        assert_eq!(Err(SharedError::Overflow), buffer.read_extents(1, usize::MAX, 512));
+
+        buffer.ext_buffer_mut()[7] = 0xFF;
+        assert_eq!(Err(SharedError::Overflow), buffer.read_extents(1, 1, 512));
+    }
+
+    #[test]
+    fn sparse_logical_size() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &[4, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+
+        assert_eq!(Ok((4 + 256) * 512), buffer.sparse_logical_size(1, 2, 512));
+        assert_eq!(Ok(4), buffer.sparse_logical_size(1, 1, 1));
+        assert_eq!(Ok(0), buffer.sparse_logical_size(17, 0, 512));
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 2, size: 16, len: 17 }), buffer.sparse_logical_size(2, 2, 1));
+        assert_eq!(Err(SharedError::Overflow), buffer.sparse_logical_size(1, 2, u64::MAX / 256));
+        assert_eq!(Err(SharedError::Overflow), buffer.sparse_logical_size(1, usize::MAX, 1));
+    }
+
+    #[test]
+    fn read_epoch_time() {
+        // seconds between 1904-01-01 (HFS+) and 1970-01-01
+        const HFS_EPOCH: i64 = -2_082_844_800;
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[0x7C, 0x25, 0xB0, 0x80, 0, 0, 0, 0]);
+        assert_eq!(Some(0x7C25_B080 + HFS_EPOCH), buffer.read_epoch_time(0, HFS_EPOCH, false));
+        assert_eq!(Some(0x80B0_257C), buffer.read_epoch_time(0, 0, true));
+        assert_eq!(Some(0x80B0_257C), buffer.read_epoch_time64(0, 0, true));
+        assert_eq!(None, buffer.read_epoch_time(6, 0, true));
+        assert_eq!(None, buffer.read_epoch_time(0, i64::MAX, true));
+        assert_eq!(None, buffer.read_epoch_time64(1, 0, true));
+    }
+
+    #[test]
+    fn check_declared_size() {
+        let buffer = StructBuffer::<[u8; 4]>::zeroed();
+        assert_eq!(Ok(()), buffer.check_declared_size(4));
+
+        let err = buffer.check_declared_size(6).unwrap_err();
+        assert_eq!(SharedError::SizeMismatch { declared: 6, actual: 4 }, err);
+        assert_eq!("declared size 6 but only 4 bytes present (2 missing)", format!("{}", err));
+
+        let err = buffer.check_declared_size(3).unwrap_err();
+        assert_eq!("declared size 3 but 4 bytes present (1 extra)", format!("{}", err));
+    }
+
+    #[test]
+    fn check_reserved() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[1, 0, 0, 2, 0, 0, 0, 3]);
+        assert_eq!(Ok(()), buffer.check_reserved(&[1..3, 4..7, 8..8]));
+        assert_eq!(Ok(()), buffer.check_reserved(&[]));
+        assert_eq!(Err(SharedError::NonZeroReserved { offset: 3 }), buffer.check_reserved(&[1..3, 2..6]));
+        let err = buffer.check_reserved(&[1..2, 4..9]).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 4, size: 5, len: 8 }, err);
+    }
+
+    #[test]
+    fn zigzag_varint() {
+        assert_eq!(Some((0, 1)), read_zigzag_i64(&[0]));
+        assert_eq!(Some((-1, 1)), read_zigzag_i64(&[1, 0xFF]));
+        assert_eq!(Some((1, 1)), read_zigzag_i64(&[2]));
+        assert_eq!(Some((-65, 2)), read_zigzag_i64(&[0x81, 0x01]));
+
+        for &value in &[0, 1, -1, 63, -64, 64, 300, -300, i64::MAX, i64::MIN] {
+            let mut encoded = Vec::new();
+            let len = push_zigzag_i64(&mut encoded, value);
+            assert_eq!(len, encoded.len());
+            assert_eq!(Some((value, len)), read_zigzag_i64(&encoded));
+        }
+
+        assert_eq!(None, read_zigzag_i64(&[]));
+        assert_eq!(None, read_zigzag_i64(&[0x80]));
+        assert_eq!(None, read_zigzag_i64(&[0x80, 0x00]));
+        assert_eq!(None, read_zigzag_i64(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]));
+        assert_eq!(None, read_zigzag_i64(&[0x80; 11]));
+    }
+
+    #[test]
+    fn fat12_pair() {
+        assert_eq!(Some((0xFF0, 0xFFF)), read_fat12_pair(&[0xF0, 0xFF, 0xFF], 0));
+        assert_eq!(Some((0x003, 0x004)), read_fat12_pair(&[0, 0x03, 0x40, 0x00], 1));
+        assert_eq!(None, read_fat12_pair(&[0, 0x03, 0x40], 1));
+
+        let mut bytes = [0_u8; 4];
+        assert_eq!(Some(()), write_fat12_pair(&mut bytes, 1, (0xABC, 0x123)));
+        assert_eq!([0, 0xBC, 0x3A, 0x12], bytes);
+        assert_eq!(Some((0xABC, 0x123)), read_fat12_pair(&bytes, 1));
+        assert_eq!(None, write_fat12_pair(&mut bytes, 2, (1, 2)));
+
+        assert_eq!(None, write_fat12_pair(&mut bytes, 0, (0x1002, 0x123)));
+        assert_eq!(None, write_fat12_pair(&mut bytes, 0, (0x002, 0x1000)));
+        assert_eq!([0, 0xBC, 0x3A, 0x12], bytes);
+    }
+
+    #[test]
+    fn read_self_relative() {
+        let buffer = StructBuffer::<[u8; 10]>::with_value(&[0, 0, 4, 0, 0, 0, 0, 0, 0, 9]);
+        assert_eq!(Some(6), buffer.read_self_relative(2, true));
+        assert_eq!(None, buffer.read_self_relative(2, false));
+        assert_eq!(Some(5), buffer.read_self_relative(5, false));
+        assert_eq!(None, buffer.read_self_relative(6, false));
+        assert_eq!(None, buffer.read_self_relative(7, true));
+
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[0, 0, 6, 0, 0, 0, 0, 0]);
+        assert_eq!(None, buffer.read_self_relative(2, true));
+    }
+
+    #[test]
+    fn read_u64_scaled() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[0, 0, 0, 0, 0, 0, 0, 8]);
+        assert_eq!(Some(8 * 512), buffer.read_u64_scaled(0, 512, false));
+        assert_eq!(Some(0), buffer.read_u64_scaled(0, 0, true));
+        assert_eq!(None, buffer.read_u64_scaled(0, 512, true));
+        assert_eq!(None, buffer.read_u64_scaled(1, 1, true));
+    }
+
+    #[test]
+    fn read_optional() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[0xFF, 0xFF, 0xFF, 0xFF, 7, 0, 0, 0]);
+        assert_eq!(Some(None), buffer.read_optional_u32(0, u32::MAX, true));
+        assert_eq!(Some(Some(7)), buffer.read_optional_u32(4, u32::MAX, true));
+        assert_eq!(Some(Some(0x0700_0000)), buffer.read_optional_u32(4, u32::MAX, false));
+        assert_eq!(None, buffer.read_optional_u32(5, u32::MAX, true));
+
+        assert_eq!(Some(None), buffer.read_optional_u16(2, 0xFFFF, false));
+        assert_eq!(Some(Some(0x07FF)), buffer.read_optional_u16(3, 0xFFFF, true));
+        assert_eq!(Some(None), buffer.read_optional_u64(0, 0x0000_0007_FFFF_FFFF, true));
+        assert_eq!(Some(Some(0x0000_0007_FFFF_FFFF)), buffer.read_optional_u64(0, 0, true));
+        assert_eq!(None, buffer.read_optional_u64(1, 0, true));
+    }
+
+    #[test]
+    fn chain_length() {
+        // each byte holds the offset of the next node, 0 terminates
+        let buffer = StructBuffer::<[u8; 6]>::with_value(&[0, 4, 0, 2, 5, 1]);
+        let next = |node: usize| buffer.buffer().get(node).copied().filter(|&next| next != 0).map(usize::from);
+
+        assert_eq!(Ok(1), buffer.chain_length(0, next, 0));
+        assert_eq!(Ok(1), buffer.chain_length(2, next, 5));
+        assert_eq!(Ok(2), buffer.chain_length(3, next, 5));
+        assert_eq!(Err(SharedError::ChainTooLong { max_hops: 5 }), buffer.chain_length(4, next, 5));
+        assert_eq!(Err(SharedError::ChainTooLong { max_hops: 0 }), buffer.chain_length(3, next, 0));
+    }
+
+    #[test]
+    fn decode_mixed() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let schema = [
+            (0, 4, Endianness::Little),
+            (4, 2, Endianness::Big),
+            (6, 2, Endianness::Little),
+            (7, 1, Endianness::Big),
+        ];
+        assert_eq!(Ok(vec![0x0403_0201, 0x0506, 0x0807, 8]), buffer.decode_mixed(&schema));
+        assert_eq!(Ok(vec![0x0102_0304_0506_0708]), buffer.decode_mixed(&[(0, 8, Endianness::Big)]));
+        assert_eq!(Ok(vec![]), buffer.decode_mixed(&[]));
+    }
+
+    #[test]
+    fn decode_mixed_invalid_field() {
+        let buffer = StructBuffer::<[u8; 8]>::zeroed();
+        assert_eq!(
+            Err(SharedError::OutOfBounds { offset: 6, size: 4, len: 8 }),
+            buffer.decode_mixed(&[(0, 2, Endianness::Big), (6, 4, Endianness::Little)])
+        );
+        assert_eq!(Err(SharedError::InvalidWidth { width: 9 }), buffer.decode_mixed(&[(0, 9, Endianness::Big)]));
+        assert_eq!(Err(SharedError::InvalidWidth { width: 0 }), buffer.decode_mixed(&[(0, 0, Endianness::Big)]));
+    }
+
+    #[test]
+    fn read_shift_size() {
+        let buffer = StructBuffer::<[u8; 6]>::with_value(&[12, 0, 63, 0, 64, 0]);
+        assert_eq!(Some(4096), buffer.read_shift_size(0, true));
+        assert_eq!(Some(1 << 63), buffer.read_shift_size(2, true));
+        assert_eq!(None, buffer.read_shift_size(4, true));
+        assert_eq!(None, buffer.read_shift_size(0, false));
+        assert_eq!(Some(1 << 63), buffer.read_shift_size(1, false));
+        assert_eq!(None, buffer.read_shift_size(5, true));
+    }
+
+    #[test]
+    fn check_version() {
+        let buffer = StructBuffer::<[u8; 4]>::with_value(&[3, 0, 0, 0]);
+        assert_eq!(Ok(3), buffer.check_version(0, 1..=3));
+
+        let err = buffer.check_version(0, 1..=2).unwrap_err();
+        assert_eq!(SharedError::UnsupportedVersion { found: 3, min: 1, max: 2 }, err);
+        assert_eq!("unsupported version 3 (supported 1..=2)", format!("{}", err));
+        assert!(buffer.check_version(1, 1..=2).is_err());
+    }
+
+    #[test]
+    fn read_directory() {
+        let mut buffer = StructBuffer::<u8>::with_value(&2);
+        buffer.insert_ext(0, &[17, 0, 0, 0, 3, 0, 0, 0, 20, 0, 0, 0, 1, 0, 0, 0]).unwrap();
+        buffer.insert_ext(16, b"abcd").unwrap();
+
+        let entries = buffer.read_directory(1, 2).unwrap();
+        assert_eq!(vec![(17, 3), (20, 1)], entries);
+        assert_eq!(Some(&b"abc"[..]), buffer.resolve_entry(entries[0]));
+        assert_eq!(Some(&b"d"[..]), buffer.resolve_entry(entries[1]));
+        assert_eq!(None, buffer.resolve_entry((20, 2)));
+
+        assert!(buffer.read_directory(1, 3).is_err());
+        assert_eq!(Err(SharedError::Overflow), buffer.read_directory(1, usize::MAX));
+
+        buffer.ext_buffer_mut()[12] = 2;
+        let err = buffer.read_directory(1, 2).unwrap_err();
+        assert_eq!(SharedError::OutOfBounds { offset: 20, size: 2, len: 21 }, err);
+    }
+}
@@ -0,0 +1,62 @@
+const GPT_SIGNATURE: &[u8] = b"EFI PART";
+const MBR_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+const MBR_SIGNATURE_OFFSET: usize = 510;
+
+/// Guesses the sector size of a raw disk image from its first bytes.
+///
+/// Heuristics, in order:
+/// * a GPT header signature (`EFI PART`) at LBA 1 for 512 and 4096 byte sectors gives that size;
+/// * an MBR boot signature (`0x55AA` at offset 510) gives 512, as it can not tell 4Kn disks apart.
+///
+/// Returns `None` when no known signature matches.
+pub fn guess_sector_size(bytes: &[u8]) -> Option<usize> {
+    for &sector_size in &[512, 4096] {
+        if bytes.get(sector_size..sector_size + GPT_SIGNATURE.len()) == Some(GPT_SIGNATURE) {
+            return Some(sector_size);
+        }
+    }
+
+    if bytes.get(MBR_SIGNATURE_OFFSET..MBR_SIGNATURE_OFFSET + 2) == Some(&MBR_SIGNATURE[..]) {
+        return Some(512);
+    }
+
+    None
+}
+
+/// Returns the number of pad bytes needed to grow `current_len` up to a multiple of `align`.
+///
+/// `align` must be a power of two.
+pub fn padding_to_align(current_len: usize, align: usize) -> usize {
+    debug_assert!(align.is_power_of_two(), "align must be a power of two");
+    current_len.wrapping_neg() & (align - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_sector_size_by_signature() {
+        let mut image = vec![0_u8; 8192];
+        assert_eq!(None, guess_sector_size(&image));
+        assert_eq!(None, guess_sector_size(&image[..100]));
+
+        image[510..512].copy_from_slice(&MBR_SIGNATURE);
+        assert_eq!(Some(512), guess_sector_size(&image));
+
+        image[4096..4104].copy_from_slice(GPT_SIGNATURE);
+        assert_eq!(Some(4096), guess_sector_size(&image));
+
+        image[512..520].copy_from_slice(GPT_SIGNATURE);
+        assert_eq!(Some(512), guess_sector_size(&image));
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(0, padding_to_align(0, 8));
+        assert_eq!(7, padding_to_align(1, 8));
+        assert_eq!(0, padding_to_align(16, 8));
+        assert_eq!(3, padding_to_align(13, 16));
+        assert_eq!(0, padding_to_align(13, 1));
+    }
+}
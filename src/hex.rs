@@ -0,0 +1,114 @@
+use crate::xstd::String;
+use crate::StructBuffer;
+use core::fmt::{self, Write};
+
+/// Writes a canonical hex+ASCII dump of `bytes`, 16 bytes per line (`hexdump -C` style).
+///
+/// Non-printable bytes are shown as `.` in the ASCII column.
+pub fn hex_dump(bytes: &[u8], out: &mut impl Write) -> fmt::Result {
+    hex_dump_at(bytes, 0, out)
+}
+
+/// The same as [`hex_dump`], but the printed offsets start at `base_offset`.
+pub(crate) fn hex_dump_at(bytes: &[u8], base_offset: usize, out: &mut impl Write) -> fmt::Result {
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        write!(out, "{:08x} ", base_offset + line * 16)?;
+        for i in 0..16 {
+            if i == 8 {
+                out.write_char(' ')?;
+            }
+            match chunk.get(i) {
+                Some(byte) => write!(out, " {:02x}", byte)?,
+                None => out.write_str("   ")?,
+            }
+        }
+
+        out.write_str("  |")?;
+        for &byte in chunk {
+            out.write_char(if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })?;
+        }
+        out.write_str("|\n")?;
+    }
+
+    Ok(())
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Writes a [`hex_dump`] of the whole buffer, the ext region included.
+    pub fn hex_dump_to(&self, out: &mut impl Write) -> fmt::Result {
+        hex_dump(self.buffer(), out)
+    }
+
+    /// Returns a labeled hex dump of the `range` bytes, printed with their absolute offsets.
+    ///
+    /// The range is clamped to the buffer.
+    pub fn dump_range(&self, range: core::ops::Range<usize>, label: &str) -> String {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+
+        let mut dump = String::new();
+        let _ = writeln!(dump, "{} [{:#x}..{:#x}]", label, start, end);
+        let _ = hex_dump_at(&self.buffer()[start..end], start, &mut dump);
+        dump
+    }
+}
+
+/// Prints the struct size, the ext size and the raw bytes in hex. `T` itself is never formatted.
+impl<T: Sized + Clone + Copy> fmt::Debug for StructBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (head, ext) = self.split();
+        let name = core::any::type_name::<T>();
+        write!(f, "StructBuffer<{}> {{ size: {}, ext: {}, bytes: [", name, head.len(), ext.len())?;
+        for (i, byte) in self.buffer().iter().enumerate() {
+            if i != 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        f.write_str("] }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_dump_partial_line() {
+        let buffer = StructBuffer::<[u8; 20]>::with_value(b"Hello, disk!\x00\x01\x7F\xFF\r\n ~");
+        let mut dump = String::new();
+        buffer.hex_dump_to(&mut dump).unwrap();
+        assert_eq!(
+            "00000000  48 65 6c 6c 6f 2c 20 64  69 73 6b 21 00 01 7f ff  |Hello, disk!....|\n\
+             00000010  0d 0a 20 7e                                       |.. ~|\n",
+            dump
+        );
+
+        let mut empty = String::new();
+        hex_dump(&[], &mut empty).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn dump_range() {
+        let buffer = StructBuffer::<[u8; 20]>::with_value(b"0123456789abcdef\x00\x01AB");
+        assert_eq!(
+            "crc [0x2..0x6]\n00000002  32 33 34 35                                       |2345|\n",
+            buffer.dump_range(2..6, "crc")
+        );
+        assert_eq!(
+            "tail [0x10..0x14]\n00000010  00 01 41 42                                       |..AB|\n",
+            buffer.dump_range(16..100, "tail")
+        );
+        assert_eq!("none [0x14..0x14]\n", buffer.dump_range(30..40, "none"));
+    }
+
+    #[test]
+    fn debug_bytes() {
+        let mut buffer = StructBuffer::<[u8; 3]>::with_value(&[0x0c, 0, 0xff]);
+        assert_eq!("StructBuffer<[u8; 3]> { size: 3, ext: 0, bytes: [0c 00 ff] }", format!("{:?}", buffer));
+
+        buffer.insert_ext(0, &[1, 0xab]).unwrap();
+        assert_eq!("StructBuffer<[u8; 3]> { size: 3, ext: 2, bytes: [0c 00 ff 01 ab] }", format!("{:?}", buffer));
+    }
+}
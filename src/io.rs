@@ -0,0 +1,180 @@
+#[cfg(feature = "checksum")]
+use crate::checksum::crc32;
+use crate::endian::read_uint;
+use crate::records::read_value;
+use crate::{Pod, StructBuffer};
+use core::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Reads exactly `size_of::<T>()` bytes from `reader` into a new buffer.
+    pub fn from_reader<R: Read>(reader: &mut R) -> io::Result<Self>
+    where
+        T: Pod,
+    {
+        Self::from_reader_with_ext(reader, 0)
+    }
+
+    /// Reads exactly `size_of::<T>() + ext_size` bytes from `reader`, the struct followed by its ext region.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the total size overflows.
+    pub fn from_reader_with_ext<R: Read>(reader: &mut R, ext_size: usize) -> io::Result<Self>
+    where
+        T: Pod,
+    {
+        let size = core::mem::size_of::<T>()
+            .checked_add(ext_size)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "buffer size overflow"))?;
+        let mut buffer = Self::zeroed();
+        buffer.buffer.resize(size, 0);
+        reader.read_exact(&mut buffer.buffer)?;
+        Ok(buffer)
+    }
+
+    /// Seeks `reader` to `offset` and reads exactly `size_of::<T>()` bytes, e.g. a header at a known
+    /// location of a disk image.
+    ///
+    /// The stream position is not restored: the cursor is left right after the struct.
+    pub fn read_from_file_at<R: Read + Seek>(reader: &mut R, offset: u64) -> io::Result<Self>
+    where
+        T: Pod,
+    {
+        reader.seek(SeekFrom::Start(offset))?;
+        Self::from_reader(reader)
+    }
+
+    /// Reads a `count_width`-byte (1 to 8) record count followed by that many `U` records from `reader`.
+    ///
+    /// A count above `max_count` is rejected with [`io::ErrorKind::InvalidData`] before anything is allocated,
+    /// which guards against absurd counts in untrusted data. A zero-sized `U` is rejected with
+    /// [`io::ErrorKind::InvalidInput`], as such a table can not be bounded by its byte size.
+    pub fn read_counted_table<R: Read, U: Pod>(
+        reader: &mut R,
+        count_width: usize,
+        little_endian: bool,
+        max_count: Option<u64>,
+    ) -> io::Result<(u64, Vec<U>)> {
+        if count_width == 0 || count_width > 8 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "unsupported count width"));
+        }
+        if core::mem::size_of::<U>() == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "zero-sized record type"));
+        }
+
+        let mut count_bytes = [0_u8; 8];
+        reader.read_exact(&mut count_bytes[..count_width])?;
+        let count = read_uint(&count_bytes, 0, count_width, little_endian).unwrap();
+        if max_count.is_some_and(|max_count| count > max_count) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "record count exceeds the maximum"));
+        }
+
+        let size = core::mem::size_of::<U>();
+        let table_size = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(size))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "table size overflow"))?;
+        let mut table = vec![0_u8; table_size];
+        reader.read_exact(&mut table)?;
+
+        let records = (0..count as usize).map(|index| read_value::<U>(&table, index * size).unwrap()).collect();
+        Ok((count, records))
+    }
+
+    /// Writes the whole buffer (struct bytes and any ext bytes) to `writer`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.buffer())
+    }
+
+    /// Writes the whole buffer followed by its CRC-32 as a 4-byte little-endian trailer.
+    #[cfg(feature = "checksum")]
+    pub fn write_to_with_crc<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_to(writer)?;
+        writer.write_all(&crc32(self.buffer()).to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_reader() {
+        let mut reader = &b"123456789"[..];
+        let buffer = StructBuffer::<[u8; 2]>::from_reader(&mut reader).unwrap();
+        assert_eq!(*b"12", buffer.copy());
+        assert!(!buffer.has_ext_buffer());
+
+        let buffer = StructBuffer::<[u8; 2]>::from_reader_with_ext(&mut reader, 3).unwrap();
+        assert_eq!(*b"34", buffer.copy());
+        assert_eq!(b"567", buffer.ext_buffer());
+
+        let err = StructBuffer::<[u8; 4]>::from_reader(&mut reader).err().unwrap();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        let err = StructBuffer::<[u8; 4]>::from_reader_with_ext(&mut reader, usize::MAX).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn read_from_file_at() {
+        let mut file = io::Cursor::new(b"0123456789");
+        let buffer = StructBuffer::<[u8; 3]>::read_from_file_at(&mut file, 4).unwrap();
+        assert_eq!(*b"456", buffer.copy());
+        assert_eq!(7, file.position());
+
+        let buffer = StructBuffer::<[u8; 3]>::read_from_file_at(&mut file, 0).unwrap();
+        assert_eq!(*b"012", buffer.copy());
+
+        let err = StructBuffer::<[u8; 3]>::read_from_file_at(&mut file, 8).err().unwrap();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_counted_table() {
+        let mut reader = &b"\x02\x00abcdX"[..];
+        let table = StructBuffer::<u8>::read_counted_table::<_, [u8; 2]>(&mut reader, 2, true, None).unwrap();
+        assert_eq!((2, vec![*b"ab", *b"cd"]), table);
+        assert_eq!(b"X", reader);
+
+        let mut reader = &b"\x00\x03abcd"[..];
+        let err = StructBuffer::<u8>::read_counted_table::<_, u8>(&mut reader, 2, false, Some(2)).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        let mut reader = &b"\x03ab"[..];
+        let err = StructBuffer::<u8>::read_counted_table::<_, u8>(&mut reader, 1, true, Some(3)).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        let mut reader = &[0xFF; 9][..];
+        let err = StructBuffer::<u8>::read_counted_table::<_, u16>(&mut reader, 8, true, None).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = StructBuffer::<u8>::read_counted_table::<_, u16>(&mut reader, 9, true, None).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+
+        let mut reader = &[0xFF; 8][..];
+        let err = StructBuffer::<u8>::read_counted_table::<_, [u8; 0]>(&mut reader, 8, true, None).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!(8, reader.len());
+    }
+
+    #[test]
+    fn write_to() {
+        let mut buffer = StructBuffer::<[u8; 2]>::with_value(b"ab");
+        buffer.insert_ext(0, b"cd").unwrap();
+
+        let mut out = Vec::new();
+        buffer.write_to(&mut out).unwrap();
+        assert_eq!(b"abcd", &out[..]);
+
+        let round_trip = StructBuffer::<[u8; 2]>::from_reader_with_ext(&mut &out[..], 2).unwrap();
+        assert_eq!(buffer.buffer(), round_trip.buffer());
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn write_to_with_crc() {
+        let buffer = StructBuffer::<[u8; 9]>::with_value(b"123456789");
+        let mut out = Vec::new();
+        buffer.write_to_with_crc(&mut out).unwrap();
+        assert_eq!(b"123456789\x26\x39\xF4\xCB", &out[..]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::xstd::{String, Vec};
+use crate::StructBuffer;
+use core::fmt::Write;
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Formats a layout table from the manually declared `(name, offset, size)` fields of `T`,
+    /// flagging padding gaps, overlapping fields and fields exceeding `size_of::<T>()`.
+    pub fn layout_report(fields: &[(&'static str, usize, usize)]) -> String {
+        let struct_size = core::mem::size_of::<T>();
+        let mut sorted: Vec<_> = fields.to_vec();
+        sorted.sort_by_key(|&(_, offset, _)| offset);
+
+        let mut report = String::new();
+        let _ = writeln!(report, "{} ({} bytes)", core::any::type_name::<T>(), struct_size);
+        let _ = writeln!(report, "offset  size  field");
+
+        let mut cursor = 0;
+        for (name, offset, size) in sorted {
+            if offset > cursor {
+                let _ = writeln!(report, "{:>6}  {:>4}  <padding>", cursor, offset - cursor);
+            }
+
+            let _ = write!(report, "{:>6}  {:>4}  {}", offset, size, name);
+            if offset < cursor {
+                let _ = write!(report, "  ! overlaps {}..{}", offset, cursor.min(offset + size));
+            }
+            if offset + size > struct_size {
+                let _ = write!(report, "  ! exceeds struct size");
+            }
+            report.push('\n');
+            cursor = cursor.max(offset + size);
+        }
+
+        if cursor < struct_size {
+            let _ = writeln!(report, "{:>6}  {:>4}  <padding>", cursor, struct_size - cursor);
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    struct Header {
+        kind: u8,
+        size: u32,
+        flags: u16,
+    }
+
+    #[test]
+    fn layout_report() {
+        let report = StructBuffer::<Header>::layout_report(&[("size", 4, 4), ("kind", 0, 1), ("flags", 8, 2)]);
+        let expected = "\
+rdisk_shared::layout::tests::Header (12 bytes)
+offset  size  field
+     0     1  kind
+     1     3  <padding>
+     4     4  size
+     8     2  flags
+    10     2  <padding>
+";
+        assert_eq!(expected, report);
+
+        let report = StructBuffer::<Header>::layout_report(&[("size", 0, 8), ("kind", 4, 1), ("flags", 10, 4)]);
+        let lines: Vec<_> = report.lines().skip(2).collect();
+        assert_eq!(
+            vec![
+                "     0     8  size",
+                "     4     1  kind  ! overlaps 4..5",
+                "     8     2  <padding>",
+                "    10     4  flags  ! exceeds struct size",
+            ],
+            lines
+        );
+    }
+}
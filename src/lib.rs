@@ -18,6 +18,33 @@ pub mod xstd {
 
 use xstd::*;
 
+mod aligned;
+pub mod bits;
+mod builder;
+pub mod checksum;
+mod diff;
+pub mod endian;
+mod error;
+mod ext;
+pub mod fields;
+pub mod geometry;
+pub mod hex;
+#[cfg(feature = "std")]
+mod io;
+mod layout;
+mod records;
+pub mod rle;
+pub mod search;
+pub mod text;
+mod tlv;
+mod tracking;
+
+pub use aligned::{alloc_buffer_aligned, AlignedBuffer};
+pub use builder::StructBufferBuilder;
+pub use error::{InsufficientBufferError, SharedError};
+pub use records::ExtRecords;
+pub use tracking::TrackingStructBuffer;
+
 pub trait NullSafePtr<T: Sized> {
     fn safe_ptr(&self) -> *const T;
 }
@@ -52,16 +79,35 @@ impl<T: Sized> NullSafePtr<T> for Vec<T> {
     }
 }
 
+impl<T: Sized> NullSafeMutPtr<T> for Vec<T> {
+    fn safe_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_slice().safe_mut_ptr()
+    }
+}
+
 impl NullSafePtr<u8> for str {
     fn safe_ptr(&self) -> *const u8 {
         self.as_bytes().safe_ptr()
     }
 }
 
+/// Returns the null-safe pointer of `slice` together with its length: `(null(), 0)` for an empty slice.
+pub fn ptr_len<T: Sized>(slice: &[T]) -> (*const T, usize) {
+    (slice.safe_ptr(), slice.len())
+}
+
+/// The same as [`ptr_len`], but also maps `None` to `(null(), 0)`.
+pub fn opt_ptr_len<T: Sized>(opt: Option<&[T]>) -> (*const T, usize) {
+    opt.map_or((core::ptr::null(), 0), ptr_len)
+}
+
 pub trait AsByteSlice {
     /// # Safety
     /// The method is unsafe because any padding bytes in the struct may be uninitialized memory (giving undefined behavior).
     /// Also, there are not any Endianness assumtions. The caller should care about it.
+    ///
+    /// `bool` and `char` are supported for serializing only: reinterpreting arbitrary bytes
+    /// as them is undefined behavior (invalid bit patterns).
     unsafe fn as_byte_slice(&self) -> &[u8];
 }
 
@@ -72,7 +118,75 @@ pub trait AsByteSliceMut {
     unsafe fn as_byte_slice_mut(&mut self) -> &mut [u8];
 }
 
-macro_rules! impl_int {
+pub trait AsStruct {
+    /// Views the front `size_of::<T>()` bytes as `&T`, or returns `None` if the slice is too short
+    /// or is not aligned for `T`.
+    ///
+    /// # Safety
+    /// The bytes must be a valid representation of `T`. There are not any Endianness assumtions.
+    unsafe fn as_struct<T: Copy>(&self) -> Option<&T>;
+}
+
+pub trait AsStructMut {
+    /// The mutable variant of [`AsStruct::as_struct`].
+    ///
+    /// # Safety
+    /// The same as for `as_struct`.
+    unsafe fn as_struct_mut<T: Copy>(&mut self) -> Option<&mut T>;
+}
+
+impl AsStruct for [u8] {
+    unsafe fn as_struct<T: Copy>(&self) -> Option<&T> {
+        records::ref_at(self, 0)
+    }
+}
+
+impl AsStructMut for [u8] {
+    unsafe fn as_struct_mut<T: Copy>(&mut self) -> Option<&mut T> {
+        let bytes = self.get_mut(..core::mem::size_of::<T>())?;
+        if bytes.as_ptr() as usize % core::mem::align_of::<T>() != 0 {
+            return None;
+        }
+
+        #[allow(clippy::cast_ptr_alignment)]
+        Some(&mut *(bytes.as_mut_ptr() as *mut T))
+    }
+}
+
+pub trait FromByteSlice: Sized {
+    /// Reads the value from the first `size_of::<Self>()` bytes of the slice.
+    ///
+    /// # Safety
+    /// The bytes must be a valid representation of `Self`. There are not any Endianness assumtions.
+    /// Panics if the slice is shorter than `size_of::<Self>()`.
+    unsafe fn from_byte_slice(bytes: &[u8]) -> Self;
+
+    /// Copies the whole slice into a `Vec<Self>`. Trailing bytes not forming a whole value are ignored.
+    ///
+    /// # Safety
+    /// The same as for `from_byte_slice`.
+    unsafe fn vec_from_byte_slice(bytes: &[u8]) -> Vec<Self>;
+}
+
+/// Marker for plain-old-data types: `Copy` types without padding bytes,
+/// for which every bit pattern (including all zeroes) is a valid value.
+///
+/// # Safety
+/// Implement only for `#[repr(C)]`/`#[repr(C, packed)]` structs (or primitives) that contain no padding,
+/// pointers, references, `bool`, `char` or enums, recursively.
+pub unsafe trait Pod: Copy {}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// `len * size_of::<U>()`. A valid slice never overflows it, so an overflow means a corrupted length:
+/// it is caught by a debug assertion, and release builds fall back to an empty slice instead of a mismatched one.
+fn slice_byte_size<U>(len: usize) -> usize {
+    let byte_size = len.checked_mul(core::mem::size_of::<U>());
+    debug_assert!(byte_size.is_some(), "Slice byte size overflow!");
+    byte_size.unwrap_or(0)
+}
+
+macro_rules! impl_as_byte_slice {
     ($name:ty) => {
         impl AsByteSlice for $name {
             unsafe fn as_byte_slice(&self) -> &[u8] {
@@ -83,17 +197,40 @@ macro_rules! impl_int {
 
         impl AsByteSlice for [$name] {
             unsafe fn as_byte_slice(&self) -> &[u8] {
-                let byte_size = self.len() * core::mem::size_of::<$name>();
+                let byte_size = slice_byte_size::<$name>(self.len());
                 core::slice::from_raw_parts(self.as_ptr() as *const u8, byte_size)
             }
         }
 
         impl AsByteSlice for Vec<$name> {
             unsafe fn as_byte_slice(&self) -> &[u8] {
-                let byte_size = self.len() * core::mem::size_of::<$name>();
+                let byte_size = slice_byte_size::<$name>(self.len());
                 core::slice::from_raw_parts(self.as_ptr() as *const u8, byte_size)
             }
         }
+    };
+}
+
+macro_rules! impl_int {
+    ($name:ty) => {
+        unsafe impl Pod for $name {}
+
+        impl FromByteSlice for $name {
+            unsafe fn from_byte_slice(bytes: &[u8]) -> Self {
+                let byte_size = core::mem::size_of::<$name>();
+                if bytes.len() < byte_size {
+                    panic!("Insufficient buffer size!")
+                }
+                core::ptr::read_unaligned(bytes.as_ptr() as *const $name)
+            }
+
+            unsafe fn vec_from_byte_slice(bytes: &[u8]) -> Vec<Self> {
+                let byte_size = core::mem::size_of::<$name>();
+                bytes.chunks_exact(byte_size).map(|chunk| Self::from_byte_slice(chunk)).collect()
+            }
+        }
+
+        impl_as_byte_slice!($name);
 
         impl AsByteSliceMut for $name {
             unsafe fn as_byte_slice_mut(&mut self) -> &mut [u8] {
@@ -104,20 +241,38 @@ macro_rules! impl_int {
 
         impl AsByteSliceMut for [$name] {
             unsafe fn as_byte_slice_mut(&mut self) -> &mut [u8] {
-                let byte_size = self.len() * core::mem::size_of::<$name>();
+                let byte_size = slice_byte_size::<$name>(self.len());
                 core::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut u8, byte_size)
             }
         }
 
         impl AsByteSliceMut for Vec<$name> {
             unsafe fn as_byte_slice_mut(&mut self) -> &mut [u8] {
-                let byte_size = self.len() * core::mem::size_of::<$name>();
+                let byte_size = slice_byte_size::<$name>(self.len());
                 core::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut u8, byte_size)
             }
         }
     };
 }
 
+impl<T, const N: usize> AsByteSlice for [T; N]
+where
+    [T]: AsByteSlice,
+{
+    unsafe fn as_byte_slice(&self) -> &[u8] {
+        self.as_slice().as_byte_slice()
+    }
+}
+
+impl<T, const N: usize> AsByteSliceMut for [T; N]
+where
+    [T]: AsByteSliceMut,
+{
+    unsafe fn as_byte_slice_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice().as_byte_slice_mut()
+    }
+}
+
 impl_int!(u8);
 impl_int!(u16);
 impl_int!(u32);
@@ -126,14 +281,25 @@ impl_int!(i8);
 impl_int!(i16);
 impl_int!(i32);
 impl_int!(i64);
+impl_int!(u128);
+impl_int!(i128);
+impl_int!(usize);
+impl_int!(isize);
+impl_int!(f32);
+impl_int!(f64);
+
+// Write direction only: not every bit pattern is a valid `bool` or `char`,
+// so there are no `AsByteSliceMut`/`FromByteSlice` implementations for them.
+impl_as_byte_slice!(bool);
+impl_as_byte_slice!(char);
 
-#[derive(Clone)]
+/// The backing storage is always aligned for `T`, so [`raw`](Self::raw) and [`raw_mut`](Self::raw_mut)
+/// give properly aligned references.
 pub struct StructBuffer<T: Sized> {
-    buffer: Vec<u8>,
+    buffer: AlignedBuffer,
     _marker: core::marker::PhantomData<T>,
 }
 
-#[allow(clippy::len_without_is_empty)]
 impl<T: Sized + Clone + Copy> StructBuffer<T> {
     /// Creates a buffer capable to hold the value of type `T`.
     ///
@@ -141,7 +307,7 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
     /// The buffer is uninitialized!
     pub unsafe fn new() -> Self {
         Self {
-            buffer: alloc_buffer(core::mem::size_of::<T>()),
+            buffer: alloc_buffer_aligned(core::mem::size_of::<T>(), core::mem::align_of::<T>()),
             _marker: Default::default(),
         }
     }
@@ -152,13 +318,15 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
     /// The buffer is uninitialized!
     pub unsafe fn with_ext(ext_size: usize) -> Self {
         Self {
-            buffer: alloc_buffer(core::mem::size_of::<T>() + ext_size),
+            buffer: alloc_buffer_aligned(core::mem::size_of::<T>() + ext_size, core::mem::align_of::<T>()),
             _marker: Default::default(),
         }
     }
 
     /// Creates a StructBuffer for the type `T` using supplied `buffer`.
     ///
+    /// The bytes are copied into storage aligned for `T`.
+    ///
     /// # Safety
     /// The buffer size should be >= mem::size_of::<T>() !
     pub unsafe fn with_buffer(buffer: Vec<u8>) -> Self {
@@ -167,9 +335,65 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
         }
 
         Self {
+            buffer: AlignedBuffer::from_slice(&buffer, core::mem::align_of::<T>()),
+            _marker: Default::default(),
+        }
+    }
+
+    /// Creates a StructBuffer for the type `T` using supplied `buffer`,
+    /// or returns an error if the buffer is smaller than mem::size_of::<T>().
+    pub fn try_with_buffer(buffer: Vec<u8>) -> Result<Self, InsufficientBufferError>
+    where
+        T: Pod,
+    {
+        if buffer.len() < core::mem::size_of::<T>() {
+            return Err(InsufficientBufferError {
+                expected: core::mem::size_of::<T>(),
+                actual: buffer.len(),
+            });
+        }
+
+        Ok(Self {
+            buffer: AlignedBuffer::from_slice(&buffer, core::mem::align_of::<T>()),
+            _marker: Default::default(),
+        })
+    }
+
+    /// Creates a StructBuffer for the type `T` reusing `buffer` as its storage (zero-copy),
+    /// or returns the buffer back if it is smaller than `size_of::<T>()` or not aligned for `T`.
+    ///
+    /// Useful for I/O that needs a stricter alignment than `T`, e.g. page-aligned direct reads.
+    pub fn try_with_aligned_buffer(buffer: AlignedBuffer) -> Result<Self, AlignedBuffer>
+    where
+        T: Pod,
+    {
+        if buffer.len() < core::mem::size_of::<T>() || buffer.align() < core::mem::align_of::<T>() {
+            return Err(buffer);
+        }
+
+        Ok(Self {
             buffer,
             _marker: Default::default(),
+        })
+    }
+
+    /// Creates a StructBuffer for the type `T` holding a copy of `bytes`, the bytes past
+    /// `size_of::<T>()` becoming the ext region, or returns an error if `bytes` is too short.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, InsufficientBufferError>
+    where
+        T: Pod,
+    {
+        if bytes.len() < core::mem::size_of::<T>() {
+            return Err(InsufficientBufferError {
+                expected: core::mem::size_of::<T>(),
+                actual: bytes.len(),
+            });
         }
+
+        Ok(Self {
+            buffer: AlignedBuffer::from_slice(bytes, core::mem::align_of::<T>()),
+            _marker: Default::default(),
+        })
     }
 
     /// Creates a StructBuffer for the type `T` using supplied `T` value.
@@ -178,9 +402,9 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
     /// The buffer size should be >= mem::size_of::<T>() !
     pub fn with_value(value: &T) -> Self {
         let buffer = unsafe {
-            let mut buffer = alloc_buffer(core::mem::size_of::<T>());
+            let mut buffer = alloc_buffer_aligned(core::mem::size_of::<T>(), core::mem::align_of::<T>());
             let value_bytes = core::slice::from_raw_parts(value as *const _ as *const u8, core::mem::size_of::<T>());
-            buffer.as_byte_slice_mut().copy_from_slice(value_bytes);
+            buffer.copy_from_slice(value_bytes);
             buffer
         };
 
@@ -191,9 +415,26 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
     }
 
     /// Creates the value of type `T` represented by the all-zero byte-pattern.
+    ///
+    /// The pattern must be a valid `T`; for [`Pod`] types prefer [`new_zeroed`](Self::new_zeroed),
+    /// which guarantees it.
     pub fn zeroed() -> Self {
         Self {
-            buffer: vec![0_u8; core::mem::size_of::<T>()],
+            buffer: AlignedBuffer::zeroed(core::mem::size_of::<T>(), core::mem::align_of::<T>()),
+            _marker: Default::default(),
+        }
+    }
+
+    /// Creates a zero-filled buffer capable to hold the value of type `T` plus `ext_size` bytes.
+    ///
+    /// The safe counterpart of [`with_ext`](Self::with_ext).
+    ///
+    /// # Panics
+    /// Panics if the buffer size overflows.
+    pub fn zeroed_with_ext(ext_size: usize) -> Self {
+        let size = core::mem::size_of::<T>().checked_add(ext_size).expect("Buffer size overflow!");
+        Self {
+            buffer: AlignedBuffer::zeroed(size, core::mem::align_of::<T>()),
             _marker: Default::default(),
         }
     }
@@ -202,6 +443,25 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
         self.buffer.len()
     }
 
+    /// Returns `true` if the buffer holds no bytes at all, which is only possible for a zero-sized `T`
+    /// without an ext region.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// The size of the struct part, always `size_of::<T>()`, usable in const contexts.
+    pub const STRUCT_SIZE: usize = core::mem::size_of::<T>();
+
+    /// The size of the struct part, always [`STRUCT_SIZE`](Self::STRUCT_SIZE).
+    pub const fn struct_size(&self) -> usize {
+        Self::STRUCT_SIZE
+    }
+
+    /// The size of the ext region, `len() - struct_size()`.
+    pub fn ext_size(&self) -> usize {
+        self.len() - self.struct_size()
+    }
+
     pub fn raw(&self) -> &T {
         #[allow(clippy::cast_ptr_alignment)]
         unsafe {
@@ -216,6 +476,13 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
         }
     }
 
+    /// Overwrites the struct bytes with `value`, leaving the ext region untouched.
+    ///
+    /// Uses an unaligned store, so it is sound regardless of the buffer alignment.
+    pub fn set_header(&mut self, value: T) {
+        unsafe { core::ptr::write_unaligned(self.buffer.as_mut_ptr() as *mut T, value) }
+    }
+
     pub fn buffer(&self) -> &[u8] {
         &self.buffer
     }
@@ -232,15 +499,134 @@ impl<T: Sized + Clone + Copy> StructBuffer<T> {
         !self.ext_buffer().is_empty()
     }
 
+    /// Splits the buffer into the struct bytes and the ext bytes.
+    pub fn split(&self) -> (&[u8], &[u8]) {
+        self.buffer.split_at(core::mem::size_of::<T>())
+    }
+
+    /// Returns the whole buffer as native-endian u16 words,
+    /// or `None` if its length is odd or it is not 2-byte aligned.
+    pub fn as_u16_slice(&self) -> Option<&[u16]> {
+        match unsafe { self.buffer.align_to::<u16>() } {
+            ([], words, []) => Some(words),
+            _ => None,
+        }
+    }
+
+    /// The mutable variant of [`as_u16_slice`](Self::as_u16_slice).
+    pub fn as_u16_slice_mut(&mut self) -> Option<&mut [u16]>
+    where
+        T: Pod,
+    {
+        match unsafe { self.buffer.align_to_mut::<u16>() } {
+            ([], words, []) => Some(words),
+            _ => None,
+        }
+    }
+
+    /// Copies the first `N` bytes of the buffer into an owned array, or `None` if the buffer is shorter.
+    pub fn to_array<const N: usize>(&self) -> Option<[u8; N]> {
+        let mut array = [0_u8; N];
+        array.copy_from_slice(self.buffer.get(..N)?);
+        Some(array)
+    }
+
+    /// Returns null-safe pointer/length pairs of the struct bytes and the ext bytes for vectored I/O.
+    ///
+    /// An empty segment yields a null pointer and zero length.
+    pub fn iovec_parts(&self) -> [(*const u8, usize); 2] {
+        let (head, ext) = self.split();
+        [ptr_len(head), ptr_len(ext)]
+    }
+
     pub fn copy(&self) -> T {
         *self.raw()
     }
 
+    /// Reads the `T` by value (unaligned) and returns it only if `validate` accepts it.
+    pub fn decode_validated(&self, validate: impl Fn(&T) -> Result<(), SharedError>) -> Result<T, SharedError> {
+        let value = unsafe { core::ptr::read_unaligned(self.buffer.as_ptr() as *const T) };
+        validate(&value)?;
+        Ok(value)
+    }
+
+    /// Consumes the buffer and returns the `T` value, moved out with an unaligned read.
+    ///
+    /// The ext region is dropped. As with `copy`, any padding bytes of `T` are read as is.
     pub fn take(self) -> T {
-        *self.raw()
+        unsafe { core::ptr::read_unaligned(self.buffer.as_ptr() as *const T) }
+    }
+
+    /// Reinterprets the buffer as a `StructBuffer<U>` reusing the backing bytes (zero-copy),
+    /// e.g. after a tag field revealed the specific header type. The ext region is resized accordingly.
+    ///
+    /// The bytes are only copied if `U` needs a stricter alignment than the storage has.
+    /// Returns the original buffer back if it is smaller than `size_of::<U>()`.
+    pub fn reinterpret<U: Pod>(self) -> Result<StructBuffer<U>, Self> {
+        if self.len() < core::mem::size_of::<U>() {
+            return Err(self);
+        }
+
+        let buffer = if self.buffer.align() < core::mem::align_of::<U>() {
+            AlignedBuffer::from_slice(&self.buffer, core::mem::align_of::<U>())
+        } else {
+            self.buffer
+        };
+        Ok(StructBuffer {
+            buffer,
+            _marker: Default::default(),
+        })
+    }
+
+    /// Consumes the buffer and returns a copy of the backing bytes (struct and ext) without reinterpreting them.
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buffer.into()
+    }
+
+    /// Consumes the buffer and returns its aligned backing storage (zero-copy).
+    pub fn into_aligned_buffer(self) -> AlignedBuffer {
+        self.buffer
+    }
+}
+
+impl<T: Pod> StructBuffer<T> {
+    /// Creates a zero-filled buffer. Unlike [`new`](Self::new) the result is always initialized.
+    ///
+    /// The bytes are the same as [`zeroed`](Self::zeroed) produces, which accepts any `T` and leaves it to
+    /// the caller to know that all zeroes are a valid `T` (not so for references or most enums).
+    /// Here the `Pod` bound guarantees it, so the zeroed value can be read without any further check.
+    pub fn new_zeroed() -> Self {
+        Self::zeroed()
+    }
+
+    /// Returns a copy of the struct using an unaligned read, so it is sound regardless of the buffer alignment.
+    pub fn copy_unaligned(&self) -> T {
+        unsafe { core::ptr::read_unaligned(self.buffer.as_ptr() as *const T) }
     }
 }
 
+impl<T: Sized + Clone + Copy> Clone for StructBuffer<T> {
+    /// Clones the whole backing buffer, the ext region included.
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Compares the whole backing buffers (struct and ext bytes) byte-wise.
+///
+/// The comparison includes any padding bytes, so buffers created with `new`/`with_ext`
+/// may compare unequal (or read uninitialized memory) unless all bytes were written.
+impl<T: Sized + Clone + Copy> PartialEq for StructBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.buffer == other.buffer
+    }
+}
+
+impl<T: Sized + Clone + Copy> Eq for StructBuffer<T> {}
+
 impl<T: Sized + Clone + Copy> core::ops::Deref for StructBuffer<T> {
     type Target = T;
 
@@ -269,6 +655,7 @@ impl<T: Sized + Clone + Copy> AsByteSliceMut for StructBuffer<T> {
 
 /// # Safety
 /// The allocated buffer is uninitialized and should be entirely rewritten before read.
+#[allow(clippy::uninit_vec)]
 pub unsafe fn alloc_buffer(size: usize) -> Vec<u8> {
     let mut buffer = Vec::with_capacity(size);
     buffer.set_len(size);
@@ -286,6 +673,28 @@ mod tests {
         word: u16,
     }
 
+    unsafe impl Pod for S {}
+
+    #[test]
+    fn safe_mut_ptr_for_vec() {
+        let mut empty: Vec<u8> = Vec::new();
+        assert!(empty.safe_mut_ptr().is_null());
+
+        let mut vec = vec![1_u16, 2];
+        assert_eq!(vec.as_mut_ptr(), vec.safe_mut_ptr());
+        assert!(!vec.safe_mut_ptr().is_null());
+    }
+
+    #[test]
+    fn ptr_len_pairs() {
+        let vec = vec![1_u32, 2];
+        assert_eq!((vec.as_ptr(), 2), ptr_len(&vec));
+        assert_eq!((core::ptr::null(), 0), ptr_len::<u32>(&[]));
+        assert_eq!((vec.as_ptr(), 2), opt_ptr_len(Some(&vec)));
+        assert_eq!((core::ptr::null(), 0), opt_ptr_len(Some(&vec[..0])));
+        assert_eq!((core::ptr::null(), 0), opt_ptr_len::<u32>(None));
+    }
+
     #[test]
     fn as_byte_slice_for_vec() {
         let vec: Vec<u8> = vec![1, 2, 3];
@@ -301,6 +710,13 @@ mod tests {
         assert_eq!(12, bytes.len());
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn slice_byte_size_overflow() {
+        slice_byte_size::<u64>(usize::MAX / 4);
+    }
+
     #[test]
     fn as_byte_slice_for_slice() {
         let vec: Vec<u8> = vec![1, 2, 3];
@@ -319,37 +735,42 @@ mod tests {
         assert_eq!(12, bytes.len());
     }
 
+    #[test]
+    fn as_byte_slice_for_array() {
+        let mut array = [1_u32, 2, 3];
+        let bytes = unsafe { array.as_byte_slice() };
+        assert_eq!(12, bytes.len());
+        assert_eq!(unsafe { array.as_slice().as_byte_slice() }, bytes);
+
+        let bytes = unsafe { array.as_byte_slice_mut() };
+        bytes[4..8].copy_from_slice(&7_u32.to_ne_bytes());
+        assert_eq!([1, 7, 3], array);
+        assert!(unsafe { [0_u16; 0].as_byte_slice() }.is_empty());
+    }
+
     #[test]
     fn as_byte_slice_for_struct() {
         let mut buffer = StructBuffer::<S>::zeroed();
         assert_eq!(3, buffer.len());
 
-        unsafe {
-            // packed fileds
-            assert_eq!(0, buffer.byte);
-            assert_eq!(0, buffer.word)
-        }
+        // packed fileds
+        assert_eq!(0, { buffer.byte });
+        assert_eq!(0, { buffer.word });
 
         buffer.byte = 12;
-        unsafe {
-            assert_eq!(12, buffer.byte);
-            assert_eq!(0, buffer.word)
-        }
+        assert_eq!(12, { buffer.byte });
+        assert_eq!(0, { buffer.word });
 
         let bytes = unsafe { buffer.as_byte_slice() };
         assert_eq!(3, bytes.len());
 
         let s = buffer.copy();
-        unsafe {
-            assert_eq!(12, s.byte);
-            assert_eq!(0, s.word)
-        }
+        assert_eq!(12, { s.byte });
+        assert_eq!(0, { s.word });
 
         let s = buffer.take();
-        unsafe {
-            assert_eq!(12, s.byte);
-            assert_eq!(0, s.word)
-        }
+        assert_eq!(12, { s.byte });
+        assert_eq!(0, { s.word });
     }
 
     #[test]
@@ -369,21 +790,273 @@ mod tests {
         let b = 4_u64;
         let bytes = unsafe { b.as_byte_slice() };
         assert_eq!(8, bytes.len());
+
+        let b = 4_u128;
+        let bytes = unsafe { b.as_byte_slice() };
+        assert_eq!(16, bytes.len());
+
+        let b = -4_i128;
+        let bytes = unsafe { b.as_byte_slice() };
+        assert_eq!(16, bytes.len());
+
+        // 4 or 8 bytes depending on the target pointer width
+        let b = 4_usize;
+        let bytes = unsafe { b.as_byte_slice() };
+        assert_eq!(core::mem::size_of::<usize>(), bytes.len());
+        assert_eq!(if cfg!(target_pointer_width = "64") { 8 } else { 4 }, bytes.len());
+
+        let b = -4_isize;
+        let bytes = unsafe { b.as_byte_slice() };
+        assert_eq!(core::mem::size_of::<isize>(), bytes.len());
+
+        let vec: Vec<usize> = vec![1, 2, 3];
+        let bytes = unsafe { vec.as_byte_slice() };
+        assert_eq!(3 * core::mem::size_of::<usize>(), bytes.len());
+
+        assert_eq!(&1.5_f32.to_ne_bytes(), unsafe { 1.5_f32.as_byte_slice() });
+        assert_eq!(&(-2.0_f64).to_ne_bytes(), unsafe { (-2.0_f64).as_byte_slice() });
+        assert_eq!(1.5, unsafe { f32::from_byte_slice(&1.5_f32.to_ne_bytes()) });
+        assert_eq!(&[1, 0], unsafe { [true, false].as_byte_slice() });
+        assert_eq!(&0x41_u32.to_ne_bytes(), unsafe { 'A'.as_byte_slice() });
+        assert_eq!(8, unsafe { vec!['a', 'b'].as_byte_slice() }.len());
+    }
+
+    #[test]
+    fn from_byte_slice() {
+        let bytes = [1_u8, 2, 3, 4, 5];
+        assert_eq!(1, unsafe { u8::from_byte_slice(&bytes) });
+        assert_eq!(u16::from_ne_bytes([1, 2]), unsafe { u16::from_byte_slice(&bytes) });
+        assert_eq!(u32::from_ne_bytes([2, 3, 4, 5]), unsafe { u32::from_byte_slice(&bytes[1..]) });
+        assert_eq!(-1, unsafe { i64::from_byte_slice(&[0xFF; 8]) });
+
+        let words = unsafe { u16::vec_from_byte_slice(&bytes) };
+        assert_eq!(vec![u16::from_ne_bytes([1, 2]), u16::from_ne_bytes([3, 4])], words);
+
+        let vec: Vec<u32> = vec![1, 2, 3];
+        let round_trip = unsafe { u32::vec_from_byte_slice(vec.as_byte_slice()) };
+        assert_eq!(vec, round_trip);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_byte_slice_insufficient() {
+        unsafe { u32::from_byte_slice(&[1, 2, 3]) };
+    }
+
+    #[repr(C, packed)]
+    #[derive(Copy, Clone)]
+    struct P {
+        byte: u8,
+        words: [u16; 2],
+    }
+
+    unsafe impl Pod for P {}
+
+    #[test]
+    fn pod_new_zeroed() {
+        let mut buffer = StructBuffer::<P>::new_zeroed();
+        assert_eq!(&[0; 5], buffer.buffer());
+
+        buffer.insert_ext(0, &[9]).unwrap();
+        buffer.set_header(P { byte: 1, words: [2_u16.to_le(), 3_u16.to_le()] });
+        let value = buffer.copy_unaligned();
+        assert_eq!((1, [2, 3]), ({ value.byte }, { value.words }.map(u16::from_le)));
+        assert_eq!(0x0102_0304, StructBuffer::<u32>::with_value(&0x0102_0304).copy_unaligned());
+    }
+
+    #[test]
+    fn as_struct() {
+        let mut bytes = [1_u8, 0, 3, 4];
+        let s = unsafe { bytes.as_struct::<S>() }.unwrap();
+        assert_eq!((1, u16::from_ne_bytes([0, 3])), (s.byte, { s.word }));
+        assert!(unsafe { bytes[2..].as_struct::<S>() }.is_none());
+
+        unsafe { bytes[1..].as_struct_mut::<S>() }.unwrap().byte = 9;
+        assert_eq!([1, 9, 3, 4], bytes);
+        assert!(unsafe { bytes[..2].as_struct_mut::<S>() }.is_none());
+
+        let words = [0_u32; 2];
+        let bytes = unsafe { words.as_byte_slice() };
+        assert_eq!(Some(&0), unsafe { bytes.as_struct::<u32>() });
+        assert_eq!(None, unsafe { bytes[1..].as_struct::<u32>() });
     }
 
     #[test]
     fn ext_buffer() {
         let mut buffer = unsafe { StructBuffer::<S>::with_ext(4) };
         assert_eq!(7, buffer.len());
+        assert_eq!((3, 4), (buffer.struct_size(), buffer.ext_size()));
+        assert!(!buffer.is_empty());
         assert!(buffer.has_ext_buffer());
         assert!(buffer.ext_buffer().len() == 4);
         assert!(buffer.ext_buffer_mut().len() == 4);
 
         let mut buffer = StructBuffer::<S>::zeroed();
         assert_eq!(3, buffer.len());
+        assert_eq!((3, 0), (buffer.struct_size(), buffer.ext_size()));
         assert!(!buffer.has_ext_buffer());
-        assert!(buffer.ext_buffer().len() == 0);
-        assert!(buffer.ext_buffer_mut().len() == 0);
+        assert!(buffer.ext_buffer().is_empty());
+        assert!(buffer.ext_buffer_mut().is_empty());
+        assert!(StructBuffer::<()>::zeroed().is_empty());
+
+        const SIZE: usize = StructBuffer::<S>::STRUCT_SIZE;
+        assert_eq!([0_u8; SIZE], StructBuffer::<[u8; SIZE]>::zeroed().copy());
+
+        let buffer = StructBuffer::<S>::zeroed_with_ext(4);
+        assert_eq!(&[0; 7], buffer.buffer());
+        assert_eq!(4, buffer.ext_size());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zeroed_with_ext_overflow() {
+        StructBuffer::<[u8; 8]>::zeroed_with_ext(usize::MAX);
+    }
+
+    #[test]
+    fn split_and_iovec_parts() {
+        let mut buffer = StructBuffer::<S>::with_value(&S { byte: 1, word: 2 });
+        assert_eq!((buffer.buffer(), &[][..]), buffer.split());
+
+        let [head, ext] = buffer.iovec_parts();
+        assert_eq!((buffer.buffer().as_ptr(), 3), head);
+        assert_eq!((core::ptr::null(), 0), ext);
+
+        buffer.insert_ext(0, &[7, 8]).unwrap();
+        let (head, ext) = buffer.split();
+        assert_eq!((3, &[7, 8][..]), (head.len(), ext));
+        assert_eq!((ext.as_ptr(), 2), buffer.iovec_parts()[1]);
+    }
+
+    #[test]
+    fn to_array() {
+        let mut buffer = StructBuffer::<S>::with_value(&S { byte: 1, word: 0x0302_u16.to_le() });
+        buffer.insert_ext(0, &[4]).unwrap();
+        assert_eq!(Some([1, 2]), buffer.to_array::<2>());
+        assert_eq!(Some([1, 2, 3, 4]), buffer.to_array::<4>());
+        assert_eq!(Some([]), buffer.to_array::<0>());
+        assert_eq!(None, buffer.to_array::<5>());
+    }
+
+    #[test]
+    fn as_u16_slice() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(&[1, 0, 2, 0]);
+        assert_eq!(Some(&[1_u16.to_le(), 2_u16.to_le()][..]), buffer.as_u16_slice());
+
+        buffer.as_u16_slice_mut().unwrap()[1] = 0x0304_u16.to_le();
+        assert_eq!([1, 0, 4, 3], buffer.copy());
+
+        buffer.insert_ext(0, &[5]).unwrap();
+        assert_eq!(None, buffer.as_u16_slice());
+        assert_eq!(None, buffer.as_u16_slice_mut());
+    }
+
+    #[test]
+    fn set_header() {
+        let mut buffer = StructBuffer::<S>::zeroed();
+        buffer.insert_ext(0, &[9, 9]).unwrap();
+        buffer.set_header(S { byte: 5, word: 0x0706_u16.to_le() });
+        assert_eq!(&[5, 6, 7, 9, 9], buffer.buffer());
+    }
+
+    #[test]
+    fn try_with_buffer() {
+        let buffer = StructBuffer::<S>::try_with_buffer(vec![1, 2, 0, 4]).unwrap();
+        assert_eq!(1, buffer.byte);
+        assert_eq!(&[4], buffer.ext_buffer());
+
+        let err = StructBuffer::<S>::try_with_buffer(vec![1, 2]).err().unwrap();
+        assert_eq!(InsufficientBufferError { expected: 3, actual: 2 }, err);
+
+        let bytes = [1, 2, 0, 4, 5];
+        let buffer = StructBuffer::<S>::from_slice(&bytes).unwrap();
+        assert_eq!((&bytes[..3], &bytes[3..]), buffer.split());
+        let err = StructBuffer::<S>::from_slice(&bytes[..1]).err().unwrap();
+        assert_eq!(InsufficientBufferError { expected: 3, actual: 1 }, err);
+    }
+
+    #[test]
+    fn clone_is_isolated() {
+        let mut buffer = StructBuffer::<S>::with_value(&S { byte: 1, word: 2 });
+        buffer.insert_ext(0, &[3, 4]).unwrap();
+
+        let snapshot = buffer.clone();
+        buffer.byte = 9;
+        buffer.ext_buffer_mut()[1] = 9;
+        buffer.insert_ext(2, &[5]).unwrap();
+
+        assert_eq!(1, snapshot.byte);
+        assert!(snapshot != buffer);
+        assert_eq!(&[3, 4], snapshot.ext_buffer());
+        assert_eq!(&[3, 9, 5], buffer.ext_buffer());
+    }
+
+    #[test]
+    fn eq_compares_bytes() {
+        let mut left = StructBuffer::<S>::with_value(&S { byte: 1, word: 2 });
+        let right = StructBuffer::<S>::try_with_buffer(left.buffer().to_vec()).unwrap();
+        assert!(left == right);
+
+        left.insert_ext(0, &[0]).unwrap();
+        assert!(left != right);
+        left.remove_ext(0..1).unwrap();
+        assert!(left == right);
+    }
+
+    #[test]
+    fn decode_validated() {
+        let buffer = StructBuffer::<S>::with_value(&S { byte: 7, word: 2 });
+        let check_byte = |s: &S| match s.byte {
+            1..=9 => Ok(()),
+            value => Err(SharedError::ValueOutOfRange { value: value.into(), min: 1, max: 9 }),
+        };
+        assert_eq!(Ok(7), buffer.decode_validated(check_byte).map(|s| s.byte));
+
+        let buffer = StructBuffer::<S>::zeroed();
+        let err = buffer.decode_validated(check_byte).err().unwrap();
+        assert_eq!(SharedError::ValueOutOfRange { value: 0, min: 1, max: 9 }, err);
+    }
+
+    #[test]
+    fn take_and_into_buffer() {
+        let mut buffer = StructBuffer::<S>::with_value(&S { byte: 1, word: 0x0302_u16.to_le() });
+        buffer.insert_ext(0, &[4]).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], buffer.clone().into_buffer());
+        assert_eq!(&[1, 2, 3, 4], &buffer.clone().into_aligned_buffer()[..]);
+
+        let s = buffer.take();
+        assert_eq!((1, 0x0302), (s.byte, u16::from_le(s.word)));
+    }
+
+    #[test]
+    fn reinterpret() {
+        let buffer = StructBuffer::<u8>::with_records_from(1, &[0x0302_u16.to_le(), 0x0504_u16.to_le()]);
+        let ptr = buffer.buffer().as_ptr();
+
+        let specific = buffer.reinterpret::<S>().ok().unwrap();
+        assert_eq!((1, 0x0302), (specific.byte, u16::from_le(specific.word)));
+        assert_eq!(&[4, 5], specific.ext_buffer());
+        assert_eq!(ptr, specific.buffer().as_ptr());
+
+        let original = specific.reinterpret::<[u8; 6]>().err().unwrap();
+        assert_eq!(&[1, 2, 3, 4, 5], original.buffer());
+
+        let widened = StructBuffer::<[u8; 8]>::zeroed().reinterpret::<u64>().ok().unwrap();
+        assert_eq!(0, widened.buffer().as_ptr() as usize % 8);
+        assert_eq!(0, widened.copy());
+    }
+
+    #[test]
+    fn storage_aligned_for_t() {
+        let mut buffer = StructBuffer::<u64>::with_value(&7);
+        buffer.pad_to_sector(4096, 0);
+        assert_eq!(0, buffer.buffer().as_ptr() as usize % 8);
+        assert_eq!(7, *buffer.raw());
+
+        let buffer = StructBuffer::<u64>::try_with_aligned_buffer(AlignedBuffer::zeroed(4096, 4096)).unwrap();
+        assert_eq!(0, buffer.buffer().as_ptr() as usize % 4096);
+        assert!(StructBuffer::<u64>::try_with_aligned_buffer(AlignedBuffer::zeroed(8, 4)).is_err());
+        assert!(StructBuffer::<u64>::try_with_aligned_buffer(AlignedBuffer::zeroed(4, 8)).is_err());
     }
 
     #[test]
@@ -391,8 +1064,8 @@ mod tests {
         let mut buffer = StructBuffer::<S>::with_value(&S{ byte: 78, word: 0x1326});
         assert_eq!(3, buffer.len());
         assert!(!buffer.has_ext_buffer());
-        assert!(buffer.ext_buffer().len() == 0);
-        assert!(buffer.ext_buffer_mut().len() == 0);
+        assert!(buffer.ext_buffer().is_empty());
+        assert!(buffer.ext_buffer_mut().is_empty());
 
         assert!( buffer.byte == 78 );
         assert!( buffer.word == 0x1326 );
@@ -0,0 +1,579 @@
+use crate::endian::read_u32_le;
+use crate::xstd::{BTreeMap, Vec};
+use crate::{AlignedBuffer, Pod, SharedError, StructBuffer};
+
+/// Copies a `U` out of `bytes` at `offset` (no alignment requirements), or `None` if it does not fit.
+pub(crate) fn read_value<U: Pod>(bytes: &[u8], offset: usize) -> Option<U> {
+    let end = offset.checked_add(core::mem::size_of::<U>())?;
+    let bytes = bytes.get(offset..end)?;
+    Some(unsafe { core::ptr::read_unaligned(bytes.as_ptr() as *const U) })
+}
+
+/// Borrows a `U` from `bytes` at `offset`, or `None` if it does not fit or is misaligned for `U`.
+///
+/// `U` is expected to be plain data valid for any bit pattern.
+pub(crate) fn ref_at<U: Copy>(bytes: &[u8], offset: usize) -> Option<&U> {
+    let end = offset.checked_add(core::mem::size_of::<U>())?;
+    let ptr = bytes.get(offset..end)?.as_ptr();
+    if ptr as usize % core::mem::align_of::<U>() != 0 {
+        return None;
+    }
+
+    #[allow(clippy::cast_ptr_alignment)]
+    Some(unsafe { &*(ptr as *const U) })
+}
+
+/// Number of whole `U` records in `len` bytes. Zero-sized records are never counted.
+pub(crate) fn record_count<U>(len: usize) -> usize {
+    match core::mem::size_of::<U>() {
+        0 => 0,
+        size => len / size,
+    }
+}
+
+/// Views `values` as raw bytes. `Pod` types have no padding, so every byte is initialized.
+pub(crate) fn values_bytes<U: Pod>(values: &[U]) -> &[u8] {
+    unsafe { core::slice::from_raw_parts(values.as_ptr() as *const u8, core::mem::size_of_val(values)) }
+}
+
+/// An iterator over the whole `U` records of an ext region, see [`StructBuffer::ext_records`].
+///
+/// Each record is copied out, so the ext bytes need not be aligned for `U`.
+#[derive(Clone)]
+pub struct ExtRecords<'a, U> {
+    bytes: &'a [u8],
+    _marker: core::marker::PhantomData<U>,
+}
+
+impl<'a, U: Pod> Iterator for ExtRecords<'a, U> {
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        if core::mem::size_of::<U>() == 0 {
+            return None;
+        }
+
+        let value = read_value::<U>(self.bytes, 0)?;
+        self.bytes = &self.bytes[core::mem::size_of::<U>()..];
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = record_count::<U>(self.bytes.len());
+        (count, Some(count))
+    }
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Creates a buffer holding `header` followed by all `entries` in the ext region.
+    ///
+    /// The ext length is `entries.len() * size_of::<U>()`.
+    pub fn with_records_from<U: Pod>(header: T, entries: &[U]) -> Self {
+        let mut buffer = Self::with_value(&header);
+        buffer.buffer.extend_from_slice(values_bytes(entries));
+        buffer
+    }
+
+    /// Creates a buffer holding `header` followed by `count` copies of `value` in the ext region.
+    ///
+    /// # Panics
+    /// Panics if `count * size_of::<U>()` overflows.
+    pub fn filled_records<U: Pod>(header: T, value: U, count: usize) -> Self {
+        let ext_size = count.checked_mul(core::mem::size_of::<U>()).expect("Table size overflow!");
+        let mut buffer = Self::with_value(&header);
+        buffer.buffer.reserve(ext_size);
+        for _ in 0..count {
+            buffer.buffer.extend_from_slice(values_bytes(core::slice::from_ref(&value)));
+        }
+        buffer
+    }
+
+    /// Reinterprets the ext region as a slice of `U` records.
+    ///
+    /// The length is `ext_buffer().len() / size_of::<U>()`: a trailing partial record is not included.
+    ///
+    /// # Safety
+    /// The ext bytes must be valid `U` values (mind the padding). There are not any Endianness assumtions.
+    ///
+    /// # Panics
+    /// Panics if the ext region is not aligned for `U`.
+    pub unsafe fn ext_as_slice<U: Copy>(&self) -> &[U] {
+        let ext = self.ext_buffer();
+        if ext.as_ptr() as usize % core::mem::align_of::<U>() != 0 {
+            panic!("Unaligned ext buffer!")
+        }
+
+        core::slice::from_raw_parts(ext.as_ptr() as *const U, record_count::<U>(ext.len()))
+    }
+
+    /// The mutable variant of [`ext_as_slice`](Self::ext_as_slice).
+    ///
+    /// # Safety
+    /// The same as for `ext_as_slice`.
+    ///
+    /// # Panics
+    /// Panics if the ext region is not aligned for `U`.
+    pub unsafe fn ext_as_slice_mut<U: Copy>(&mut self) -> &mut [U] {
+        let ext = self.ext_buffer_mut();
+        if ext.as_ptr() as usize % core::mem::align_of::<U>() != 0 {
+            panic!("Unaligned ext buffer!")
+        }
+
+        core::slice::from_raw_parts_mut(ext.as_mut_ptr() as *mut U, record_count::<U>(ext.len()))
+    }
+
+    /// Returns the flexible array member following the header (`struct { T header; U data[]; }`),
+    /// i.e. the whole `U` elements of the ext region.
+    ///
+    /// Returns an empty slice if the ext region is smaller than `size_of::<U>()` or is not aligned for `U`.
+    pub fn flex_array<U: Pod>(&self) -> &[U] {
+        let ext = self.ext_buffer();
+        let aligned = ext.as_ptr() as usize % core::mem::align_of::<U>() == 0;
+        if ext.len() < core::mem::size_of::<U>() || !aligned {
+            return &[];
+        }
+
+        unsafe { self.ext_as_slice() }
+    }
+
+    /// Decodes the ext region as a row-major `rows x cols` grid of `U` values.
+    ///
+    /// Returns `None` if the ext size is not exactly `rows * cols * size_of::<U>()` or the product overflows.
+    /// `U` must not be zero-sized and `rows` may not exceed the ext size, which bounds the allocation
+    /// even for a grid without columns.
+    pub fn ext_grid<U: Pod>(&self, rows: usize, cols: usize) -> Option<Vec<Vec<U>>> {
+        let size = core::mem::size_of::<U>();
+        if size == 0 || rows > self.ext_size() || rows.checked_mul(cols)?.checked_mul(size)? != self.ext_size() {
+            return None;
+        }
+
+        let row_size = cols * size;
+
+        let ext = self.ext_buffer();
+        let value = |row: usize, col: usize| read_value::<U>(ext, row * row_size + col * size).unwrap();
+        Some((0..rows).map(|row| (0..cols).map(|col| value(row, col)).collect()).collect())
+    }
+
+    /// Iterates over the whole `U` records of the ext region with their absolute offsets,
+    /// measured from the buffer start (the struct bytes included). A trailing partial record is skipped.
+    pub fn ext_records_with_offset<'a, U: Pod + 'a>(&'a self) -> impl Iterator<Item = (usize, U)> + 'a {
+        let struct_size = self.struct_size();
+        let size = core::mem::size_of::<U>();
+        self.ext_records::<U>().enumerate().map(move |(index, value)| (struct_size + index * size, value))
+    }
+
+    /// Iterates over the whole `U` records of the ext region, stopping before a trailing partial one.
+    pub fn ext_records<U: Pod>(&self) -> ExtRecords<'_, U> {
+        ExtRecords {
+            bytes: self.ext_buffer(),
+            _marker: Default::default(),
+        }
+    }
+
+    /// Creates a buffer from a header and its owned records, the inverse of
+    /// [`into_record_vec`](Self::into_record_vec).
+    pub fn from_header_and_records<U: Pod>(header: T, records: Vec<U>) -> Self {
+        Self::with_records_from(header, &records)
+    }
+
+    /// Splits the buffer into the header value and the ext region decoded as `U` records.
+    ///
+    /// A trailing partial record in the ext region is dropped.
+    pub fn into_record_vec<U: Pod>(self) -> (T, Vec<U>) {
+        let header = unsafe { core::ptr::read_unaligned(self.buffer.as_ptr() as *const T) };
+        (header, self.ext_records::<U>().collect())
+    }
+
+    /// Splits the whole backing buffer into owned `N`-byte sectors.
+    ///
+    /// A trailing partial sector (when `len() % N != 0`) is dropped.
+    pub fn into_sectors<const N: usize>(self) -> Vec<StructBuffer<[u8; N]>> {
+        if N == 0 {
+            return Vec::new();
+        }
+
+        self.buffer
+            .chunks_exact(N)
+            .map(|sector| StructBuffer {
+                buffer: AlignedBuffer::from_slice(sector, 1),
+                _marker: Default::default(),
+            })
+            .collect()
+    }
+
+    /// Copies the `U` sub-record at `offset` out of the buffer (no alignment requirements),
+    /// or returns `None` if it does not fit.
+    pub fn read_at<U: Pod>(&self, offset: usize) -> Option<U> {
+        read_value::<U>(&self.buffer, offset)
+    }
+
+    /// Overwrites the `size_of::<U>()` bytes at `offset` with `value`,
+    /// or returns `None` (leaving the buffer untouched) if they do not fit.
+    pub fn write_at<U: Pod>(&mut self, offset: usize, value: U) -> Option<()>
+    where
+        T: Pod,
+    {
+        let end = offset.checked_add(core::mem::size_of::<U>())?;
+        self.buffer.get_mut(offset..end)?.copy_from_slice(values_bytes(core::slice::from_ref(&value)));
+        Some(())
+    }
+
+    /// Reads the little-endian u32 pointer at `table_offset + index * 4` and decodes the `U` it points to.
+    ///
+    /// Returns `None` if either the table entry or the pointed-to record is out of bounds.
+    pub fn read_indirect<U: Pod>(&self, table_offset: usize, index: usize) -> Option<U> {
+        let entry = table_offset.checked_add(index.checked_mul(4)?)?;
+        let pointer = read_u32_le(&self.buffer, entry)?;
+        read_value::<U>(&self.buffer, pointer as usize)
+    }
+
+    /// Decodes `count` child records `C` starting at `children_offset + start_index * size_of::<C>()`,
+    /// resolving a parent record's reference into a child table.
+    pub fn child_records<C: Pod>(
+        &self,
+        children_offset: usize,
+        start_index: usize,
+        count: usize,
+    ) -> Result<Vec<C>, SharedError> {
+        let size = core::mem::size_of::<C>();
+        let start = start_index
+            .checked_mul(size)
+            .and_then(|skip| children_offset.checked_add(skip))
+            .ok_or(SharedError::Overflow)?;
+        let table_size = count.checked_mul(size).ok_or(SharedError::Overflow)?;
+        if start.checked_add(table_size).ok_or(SharedError::Overflow)? > self.len() {
+            return Err(self.out_of_bounds(start, table_size));
+        }
+
+        Ok((0..count).map(|index| read_value::<C>(&self.buffer, start + index * size).unwrap()).collect())
+    }
+
+    /// Returns whether every `U` entry of a table starting at `table_offset` is naturally aligned
+    /// in memory, i.e. whether borrowing the entries in place would be sound.
+    pub fn validate_record_alignment<U>(&self, table_offset: usize) -> bool {
+        let align = core::mem::align_of::<U>();
+        table_offset <= self.len()
+            && (self.buffer.as_ptr() as usize).wrapping_add(table_offset) % align == 0
+            && core::mem::size_of::<U>() % align == 0
+    }
+
+    /// Builds an index of the ext region `U` records: `key_fn(record)` maps to the record's
+    /// byte offset from the start of the buffer. For duplicate keys the first record wins.
+    pub fn index_records<U: Pod, K: Ord>(&self, key_fn: impl Fn(&U) -> K) -> BTreeMap<K, usize> {
+        let mut index = BTreeMap::new();
+        let size = core::mem::size_of::<U>();
+        for (i, record) in self.ext_records::<U>().enumerate() {
+            index.entry(key_fn(&record)).or_insert(core::mem::size_of::<T>() + i * size);
+        }
+        index
+    }
+
+    /// Iterates the ext region as variable-length entries: a header `H` followed by
+    /// `name_len_fn(header)` name bytes, yielding `(header, name_bytes)`.
+    ///
+    /// Headers are copied out, so they need not be aligned for `H`. Iteration stops at a truncated final entry.
+    pub fn iter_var_entries<'a, H: Pod + 'a>(
+        &'a self,
+        name_len_fn: impl Fn(&H) -> usize + 'a,
+    ) -> impl Iterator<Item = (H, &'a [u8])> + 'a {
+        let ext = self.ext_buffer();
+        let mut pos = 0;
+        core::iter::from_fn(move || {
+            if core::mem::size_of::<H>() == 0 {
+                return None;
+            }
+
+            let header = read_value::<H>(ext, pos)?;
+            let name_start = pos + core::mem::size_of::<H>();
+            let name_end = name_start.checked_add(name_len_fn(&header))?;
+            let name = ext.get(name_start..name_end)?;
+            pos = name_end;
+            Some((header, name))
+        })
+    }
+
+    /// Decodes the ext region as consecutive `U` records and calls `f` for each one,
+    /// stopping at the first error. A trailing partial record is skipped.
+    pub fn try_for_each_record<U: Pod, E>(&self, mut f: impl FnMut(&U) -> Result<(), E>) -> Result<(), E> {
+        for record in self.ext_records::<U>() {
+            f(&record)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the ext region as consecutive `U` records up to (but excluding) the first one
+    /// for which `is_sentinel` returns true, or up to the end of the ext region.
+    pub fn records_until<U: Pod>(&self, is_sentinel: impl Fn(&U) -> bool) -> Vec<U> {
+        self.ext_records::<U>().take_while(|record| !is_sentinel(record)).collect()
+    }
+
+    /// Decodes the ext region as consecutive `U` records while `pred` returns true,
+    /// stopping at the first record it rejects. A trailing partial record is skipped.
+    pub fn records_while<U: Pod>(&self, pred: impl Fn(&U) -> bool) -> Vec<U> {
+        self.ext_records::<U>().take_while(|record| pred(record)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ext_as_slice() {
+        let mut buffer = StructBuffer::<u8>::with_records_from(0, &[1_u8, 2, 3, 4, 5]);
+        assert_eq!(&[[1, 2], [3, 4]], unsafe { buffer.ext_as_slice::<[u8; 2]>() });
+
+        unsafe { buffer.ext_as_slice_mut::<[u8; 2]>()[1] = [9, 9] };
+        assert_eq!(&[0, 1, 2, 9, 9, 5], buffer.buffer());
+        assert!(unsafe { buffer.ext_as_slice::<[u8; 6]>() }.is_empty());
+        assert!(unsafe { buffer.ext_as_slice::<()>() }.is_empty());
+
+        let buffer = StructBuffer::<u32>::with_records_from(0, &[7_u32, 8]);
+        if buffer.ext_buffer().as_ptr() as usize % 4 == 0 {
+            assert_eq!(&[7, 8], unsafe { buffer.ext_as_slice::<u32>() });
+        }
+    }
+
+    #[test]
+    fn flex_array() {
+        let buffer = StructBuffer::<u8>::with_records_from(3, &[[1_u8, 2], [3, 4], [5, 6]]);
+        assert_eq!(&[[1, 2], [3, 4], [5, 6]], buffer.flex_array::<[u8; 2]>());
+        assert_eq!(&[[1, 2, 3, 4]], buffer.flex_array::<[u8; 4]>());
+        assert!(buffer.flex_array::<[u8; 7]>().is_empty());
+        assert!(StructBuffer::<u8>::zeroed().flex_array::<u8>().is_empty());
+
+        let buffer = StructBuffer::<u32>::with_records_from(2, &[7_u32, 8]);
+        let aligned = buffer.ext_buffer().as_ptr() as usize % 4 == 0;
+        assert_eq!(if aligned { &[7, 8][..] } else { &[] }, buffer.flex_array::<u32>());
+    }
+
+    #[test]
+    fn ext_grid() {
+        let buffer = StructBuffer::<u8>::with_records_from(0, &[1_u8, 2, 3, 4, 5, 6]);
+        assert_eq!(Some(vec![vec![1, 2, 3], vec![4, 5, 6]]), buffer.ext_grid::<u8>(2, 3));
+        assert_eq!(Some(vec![vec![[1, 2]], vec![[3, 4]], vec![[5, 6]]]), buffer.ext_grid::<[u8; 2]>(3, 1));
+        assert_eq!(None, buffer.ext_grid::<u8>(2, 2));
+        assert_eq!(None, buffer.ext_grid::<u8>(usize::MAX, 2));
+
+        assert_eq!(None, buffer.ext_grid::<[u8; 0]>(2, 3));
+
+        let buffer = StructBuffer::<u8>::zeroed();
+        assert_eq!(Some(vec![]), buffer.ext_grid::<u8>(0, 0));
+        assert_eq!(Some(vec![]), buffer.ext_grid::<u16>(0, 5));
+        assert_eq!(None, buffer.ext_grid::<u8>(2, 0));
+        assert_eq!(None, buffer.ext_grid::<u8>(usize::MAX, 0));
+        assert_eq!(None, buffer.ext_grid::<[u8; 0]>(usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn ext_records_with_offset() {
+        let buffer = StructBuffer::<[u8; 3]>::with_records_from([0; 3], &[1_u8, 2, 3, 4, 5]);
+        let records: Vec<_> = buffer.ext_records_with_offset::<[u8; 2]>().collect();
+        assert_eq!(vec![(3, [1, 2]), (5, [3, 4])], records);
+        assert_eq!(Some(&[3, 4][..]), buffer.buffer().get(5..7));
+        assert_eq!(0, buffer.ext_records_with_offset::<[u8; 0]>().count());
+    }
+
+    #[test]
+    fn ext_records() {
+        let buffer = StructBuffer::<u8>::with_records_from(0, &[1_u16, 2, 3]);
+        assert_eq!(6, buffer.ext_size());
+        let records = buffer.ext_records::<u16>();
+        assert_eq!((3, Some(3)), records.size_hint());
+        assert_eq!(vec![1, 2, 3], records.collect::<Vec<_>>());
+
+        let mut buffer = buffer;
+        buffer.insert_ext(6, &[4]).unwrap();
+        assert_eq!(3, buffer.ext_records::<u16>().count());
+        assert_eq!(1, buffer.ext_records::<[u8; 4]>().count());
+        assert_eq!(0, buffer.ext_records::<[u8; 0]>().count());
+    }
+
+    #[test]
+    fn into_sectors() {
+        let buffer = StructBuffer::<[u8; 2]>::with_records_from([1, 2], &[3_u8, 4, 5, 6, 7]);
+        let sectors = buffer.clone().into_sectors::<3>();
+        assert_eq!(2, sectors.len());
+        assert_eq!([1, 2, 3], sectors[0].copy());
+        assert_eq!([4, 5, 6], sectors[1].copy());
+
+        assert_eq!(7, buffer.clone().into_sectors::<1>().len());
+        assert!(buffer.into_sectors::<0>().is_empty());
+    }
+
+    #[test]
+    fn iter_var_entries() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &[3, b'a', b'b', b'c', 0, 1, b'x', 5, b'y']).unwrap();
+
+        let entries: Vec<_> = buffer.iter_var_entries::<u8>(|&len| len as usize).collect();
+        assert_eq!(vec![(3, &b"abc"[..]), (0, &b""[..]), (1, &b"x"[..])], entries);
+        assert_eq!(0, buffer.iter_var_entries::<u8>(|_| usize::MAX).count());
+        assert_eq!(0, buffer.iter_var_entries::<[u8; 0]>(|_| 0).count());
+    }
+
+    #[test]
+    fn iter_var_entries_unaligned_header() {
+        #[repr(C, packed)]
+        #[derive(Copy, Clone)]
+        struct Entry {
+            kind: u8,
+            name_len: u16,
+        }
+
+        unsafe impl Pod for Entry {}
+
+        // Both headers sit at odd buffer offsets, misaligned for `u16`.
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &[1, 2, 0, b'a', b'b', 7, 1, 0, b'z']).unwrap();
+
+        let entries: Vec<_> = buffer
+            .iter_var_entries::<Entry>(|entry| u16::from_le(entry.name_len) as usize)
+            .map(|(entry, name)| (entry.kind, name))
+            .collect();
+        assert_eq!(vec![(1, &b"ab"[..]), (7, &b"z"[..])], entries);
+    }
+
+    #[test]
+    fn read_write_at() {
+        let mut buffer = StructBuffer::<u8>::with_records_from(1, &[2_u8, 3, 4]);
+        assert_eq!(Some([2, 3]), buffer.read_at::<[u8; 2]>(1));
+        assert_eq!(Some(u16::from_ne_bytes([3, 4])), buffer.read_at::<u16>(2));
+        assert_eq!(None, buffer.read_at::<u16>(3));
+        assert_eq!(None, buffer.read_at::<u8>(usize::MAX));
+
+        assert_eq!(Some(()), buffer.write_at(2, [8_u8, 9]));
+        assert_eq!(&[1, 2, 8, 9], buffer.buffer());
+        assert_eq!(None, buffer.write_at(3, 0_u16));
+        assert_eq!(None, buffer.write_at(usize::MAX, 0_u8));
+        assert_eq!(&[1, 2, 8, 9], buffer.buffer());
+    }
+
+    #[test]
+    fn read_indirect() {
+        let buffer = StructBuffer::<[u8; 11]>::with_value(&[8, 0, 0, 0, 10, 0, 0, 0, 0xAA, 0xBB, 0xCC]);
+        assert_eq!(Some([0xAA, 0xBB]), buffer.read_indirect::<[u8; 2]>(0, 0));
+        assert_eq!(Some(0xCC_u8), buffer.read_indirect::<u8>(0, 1));
+        assert_eq!(None, buffer.read_indirect::<[u8; 2]>(0, 1));
+        assert_eq!(None, buffer.read_indirect::<u8>(0, 2));
+        assert_eq!(None, buffer.read_indirect::<u8>(0, usize::MAX));
+    }
+
+    #[test]
+    fn child_records() {
+        let buffer = StructBuffer::<u8>::with_records_from(0, &[[1_u8, 1], [2, 2], [3, 3], [4, 4]]);
+        assert_eq!(Ok(vec![[2, 2], [3, 3]]), buffer.child_records::<[u8; 2]>(1, 1, 2));
+        assert_eq!(Ok(vec![]), buffer.child_records::<[u8; 2]>(1, 4, 0));
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 5, size: 6, len: 9 }), buffer.child_records::<[u8; 2]>(1, 2, 3));
+        assert_eq!(Err(SharedError::Overflow), buffer.child_records::<[u8; 2]>(1, usize::MAX, 1));
+        assert_eq!(Err(SharedError::Overflow), buffer.child_records::<[u8; 2]>(1, 0, usize::MAX));
+    }
+
+    #[test]
+    fn validate_record_alignment() {
+        let buffer = StructBuffer::<[u8; 8]>::zeroed();
+        let base = buffer.buffer().as_ptr() as usize;
+        assert_eq!((base + 1) % 4 == 0, buffer.validate_record_alignment::<u32>(1));
+        assert_eq!(base % 2 == 0, buffer.validate_record_alignment::<u16>(4));
+        assert!(buffer.validate_record_alignment::<u8>(3));
+        assert!(!buffer.validate_record_alignment::<u8>(9));
+    }
+
+    #[test]
+    fn index_records() {
+        let buffer = StructBuffer::<u8>::with_records_from(0, &[[7_u8, 1], [3, 2], [7, 3]]);
+        let index = buffer.index_records::<[u8; 2], u8>(|record| record[0]);
+        assert_eq!(2, index.len());
+        assert_eq!(Some(&1), index.get(&7));
+        assert_eq!(Some(&3), index.get(&3));
+    }
+
+    #[test]
+    fn try_for_each_record() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, &[1, 0, 2, 0, 3, 0, 4]).unwrap();
+
+        let mut seen = vec![];
+        let result: Result<(), ()> = buffer.try_for_each_record::<u16, _>(|r| {
+            seen.push(*r);
+            Ok(())
+        });
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec![1_u16.to_le(), 2_u16.to_le(), 3_u16.to_le()], seen);
+
+        let mut count = 0;
+        let result = buffer.try_for_each_record::<u8, _>(|&r| {
+            count += 1;
+            if r == 2 {
+                Err(r)
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(Err(2), result);
+        assert_eq!(3, count);
+    }
+
+    #[test]
+    fn records_until() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        assert!(buffer.records_until::<u8>(|&r| r == 0).is_empty());
+
+        buffer.insert_ext(0, &[3, 2, 0, 1]).unwrap();
+        assert_eq!(vec![3, 2], buffer.records_until::<u8>(|&r| r == 0));
+        assert_eq!(vec![3, 2, 0, 1], buffer.records_until::<u8>(|&r| r == 0xFF));
+        assert_eq!(vec![[3, 2]], buffer.records_until::<[u8; 2]>(|r| r == &[0, 1]));
+    }
+
+    #[test]
+    fn records_while() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        assert!(buffer.records_while::<u8>(|_| true).is_empty());
+
+        buffer.insert_ext(0, &[1, 3, 4, 5, 7]).unwrap();
+        assert_eq!(vec![1, 3], buffer.records_while::<u8>(|r| r % 2 == 1));
+        assert_eq!(vec![[1, 3], [4, 5]], buffer.records_while::<[u8; 2]>(|_| true));
+    }
+
+    #[test]
+    fn with_records_from() {
+        let buffer = StructBuffer::<[u8; 3]>::with_records_from([9, 8, 7], &[1_u16, 2, 3]);
+        assert_eq!(9, buffer.len());
+        assert_eq!([9, 8, 7], buffer.copy());
+        assert_eq!(vec![1_u16, 2, 3], buffer.records_until::<u16>(|_| false));
+
+        let buffer = StructBuffer::<u8>::with_records_from::<u32>(1, &[]);
+        assert!(!buffer.has_ext_buffer());
+    }
+
+    #[test]
+    fn record_vec_round_trip() {
+        let buffer = StructBuffer::<[u8; 2]>::from_header_and_records([1, 2], vec![[3_u8, 4], [5, 6]]);
+        assert_eq!(&[1, 2, 3, 4, 5, 6], buffer.buffer());
+
+        let (header, mut records) = buffer.into_record_vec::<[u8; 2]>();
+        assert_eq!([1, 2], header);
+        assert_eq!(vec![[3, 4], [5, 6]], records);
+
+        records.pop();
+        let buffer = StructBuffer::from_header_and_records(header, records);
+        assert_eq!((header, Vec::<[u8; 3]>::new()), buffer.clone().into_record_vec());
+        assert_eq!(4, buffer.len());
+    }
+
+    #[test]
+    fn filled_records() {
+        let buffer = StructBuffer::<u8>::filled_records(5, [1_u8, 2], 3);
+        assert_eq!(&[5, 1, 2, 1, 2, 1, 2], buffer.buffer());
+
+        let buffer = StructBuffer::<u8>::filled_records(5, 0_u32, 0);
+        assert_eq!(1, buffer.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn filled_records_overflow() {
+        StructBuffer::<u8>::filled_records(5, 0_u32, usize::MAX);
+    }
+}
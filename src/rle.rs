@@ -0,0 +1,68 @@
+use crate::endian::read_u32_le;
+use crate::xstd::Vec;
+use crate::StructBuffer;
+
+/// Encodes `bytes` as `(count, value)` pairs.
+///
+/// A run of 1 to 255 bytes is stored as `count, value`. Longer runs are escaped as
+/// `0, value` followed by the run length as a little-endian u32; runs longer than `u32::MAX` are split.
+pub fn encode_rle(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut rest = bytes;
+    while let Some(&value) = rest.first() {
+        let run = rest.iter().take(u32::MAX as usize).take_while(|&&byte| byte == value).count();
+        if run <= usize::from(u8::MAX) {
+            encoded.extend_from_slice(&[run as u8, value]);
+        } else {
+            encoded.extend_from_slice(&[0, value]);
+            encoded.extend_from_slice(&(run as u32).to_le_bytes());
+        }
+        rest = &rest[run..];
+    }
+    encoded
+}
+
+/// Decodes the output of [`encode_rle`], or returns `None` if `encoded` is truncated.
+pub fn decode_rle(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut pos = 0;
+    while pos < encoded.len() {
+        let value = *encoded.get(pos + 1)?;
+        let (run, pair_size) = match encoded[pos] {
+            0 => (read_u32_le(encoded, pos + 2)? as usize, 6),
+            count => (usize::from(count), 2),
+        };
+        decoded.resize(decoded.len() + run, value);
+        pos += pair_size;
+    }
+    Some(decoded)
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Run-length encodes the whole buffer, see [`encode_rle`].
+    pub fn encode_rle(&self) -> Vec<u8> {
+        encode_rle(self.buffer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_round_trip() {
+        let buffer = StructBuffer::<[u8; 5]>::with_value(&[7, 7, 7, 0, 1]);
+        assert_eq!(vec![3, 7, 1, 0, 1, 1], buffer.encode_rle());
+        assert_eq!(Some(buffer.buffer().to_vec()), decode_rle(&buffer.encode_rle()));
+
+        let mut sector = vec![0_u8; 512];
+        sector[300] = 0xE5;
+        let encoded = encode_rle(&sector);
+        assert_eq!(vec![0, 0, 44, 1, 0, 0, 1, 0xE5, 211, 0], encoded);
+        assert_eq!(Some(sector), decode_rle(&encoded));
+
+        assert_eq!(Some(vec![]), decode_rle(&encode_rle(&[])));
+        assert_eq!(None, decode_rle(&[3]));
+        assert_eq!(None, decode_rle(&[0, 0, 44, 1, 0]));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::xstd::Vec;
+use crate::StructBuffer;
+
+/// Returns every offset where `needle` occurs in `bytes`, overlapping matches included.
+///
+/// Uses the Boyer-Moore-Horspool search. An empty `needle` never matches.
+pub fn find_all(bytes: &[u8], needle: &[u8]) -> Vec<usize> {
+    let mut found = Vec::new();
+    let last = match needle.len().checked_sub(1) {
+        Some(last) if needle.len() <= bytes.len() => last,
+        _ => return found,
+    };
+
+    let mut skip = [needle.len(); 256];
+    for (i, &byte) in needle[..last].iter().enumerate() {
+        skip[usize::from(byte)] = last - i;
+    }
+
+    let mut pos = 0;
+    while pos + last < bytes.len() {
+        if &bytes[pos..=pos + last] == needle {
+            found.push(pos);
+        }
+        pos += skip[usize::from(bytes[pos + last])];
+    }
+    found
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Returns every offset of `needle` in the whole buffer, see [`find_all`].
+    pub fn find_all(&self, needle: &[u8]) -> Vec<usize> {
+        find_all(self.buffer(), needle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_all_matches() {
+        assert_eq!(vec![0, 9], find_all(b"EFI PARTxEFI PART", b"EFI PART"));
+        assert_eq!(vec![0, 1, 2], find_all(b"aaaa", b"aa"));
+        assert_eq!(vec![2], find_all(b"abcab", b"cab"));
+        assert!(find_all(b"abc", b"abcd").is_empty());
+        assert!(find_all(b"abc", b"").is_empty());
+        assert!(find_all(b"", b"a").is_empty());
+
+        let mut image = vec![0_u8; 2048];
+        image[510..512].copy_from_slice(&[0x55, 0xAA]);
+        image[1534..1536].copy_from_slice(&[0x55, 0xAA]);
+        assert_eq!(vec![510, 1534], find_all(&image, &[0x55, 0xAA]));
+    }
+
+    #[test]
+    fn struct_buffer_find_all() {
+        let buffer = StructBuffer::<[u8; 4]>::with_records_from(*b"MAGI", b"CMAGIC");
+        assert_eq!(vec![0, 5], buffer.find_all(b"MAGIC"));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::endian::Endianness;
+use crate::ext::check_range;
+use crate::xstd::Vec;
+use crate::{SharedError, StructBuffer};
+
+/// Strips a leading byte-order mark and reports the UTF-16 byte order it declares.
+///
+/// A UTF-8 BOM (`EF BB BF`) is stripped as well, but reports `None` as UTF-8 has no byte order.
+/// Without a BOM the bytes are returned unchanged with `None`.
+pub fn strip_bom(bytes: &[u8]) -> (&[u8], Option<Endianness>) {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => (rest, None),
+        [0xFF, 0xFE, rest @ ..] => (rest, Some(Endianness::Little)),
+        [0xFE, 0xFF, rest @ ..] => (rest, Some(Endianness::Big)),
+        _ => (bytes, None),
+    }
+}
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Splits the ext region into NUL-terminated UTF-8 strings (environment-block style).
+    ///
+    /// The list ends at the first empty string (a double NUL) or at the end of the region,
+    /// so the empty segment after the final NUL and any bytes after the terminator are not included.
+    /// Strings that are not valid UTF-8 are skipped.
+    pub fn ext_cstr_list(&self) -> Vec<&str> {
+        self.ext_buffer()
+            .split(|&byte| byte == 0)
+            .take_while(|segment| !segment.is_empty())
+            .filter_map(|segment| core::str::from_utf8(segment).ok())
+            .collect()
+    }
+
+    /// Returns how many bytes at the end of `range` equal `fill`, e.g. the space or NUL padding of a field.
+    ///
+    /// Fails if `range` is reversed or out of the buffer bounds.
+    pub fn trailing_fill_count(&self, range: core::ops::Range<usize>, fill: u8) -> Result<usize, SharedError> {
+        check_range(&range, self.len())?;
+        Ok(self.buffer[range].iter().rev().take_while(|&&byte| byte == fill).count())
+    }
+
+    /// Returns the minimum and maximum byte values of the whole buffer, or `None` if it is empty.
+    ///
+    /// A quick way to characterize data, e.g. all bytes in `0x20..=0x7E` suggest ASCII text.
+    pub fn byte_range(&self) -> Option<(u8, u8)> {
+        let first = *self.buffer.first()?;
+        Some(self.buffer.iter().fold((first, first), |(min, max), &byte| (min.min(byte), max.max(byte))))
+    }
+
+    /// Reads a Pascal string at `offset`: a length byte followed by that many bytes.
+    ///
+    /// Returns `None` if the declared length overruns the buffer.
+    pub fn read_pascal_str(&self, offset: usize) -> Option<&[u8]> {
+        let len = usize::from(*self.buffer.get(offset)?);
+        self.buffer.get(offset + 1..offset + 1 + len)
+    }
+
+    /// The same as [`read_pascal_str`](Self::read_pascal_str), but also requires valid UTF-8.
+    pub fn read_pascal_str_utf8(&self, offset: usize) -> Option<&str> {
+        core::str::from_utf8(self.read_pascal_str(offset)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ext_cstr_list() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        assert!(buffer.ext_cstr_list().is_empty());
+
+        buffer.insert_ext(0, b"PATH=/bin\0\xFF\xFE\0HOME=/root\0").unwrap();
+        assert_eq!(vec!["PATH=/bin", "HOME=/root"], buffer.ext_cstr_list());
+
+        buffer.insert_ext(buffer.ext_buffer().len(), b"TAIL").unwrap();
+        assert_eq!(vec!["PATH=/bin", "HOME=/root", "TAIL"], buffer.ext_cstr_list());
+    }
+
+    #[test]
+    fn ext_cstr_list_stops_at_terminator() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, b"A=1\0B=2\0\0junk\0C=3\0").unwrap();
+        assert_eq!(vec!["A=1", "B=2"], buffer.ext_cstr_list());
+
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        buffer.insert_ext(0, b"\0A=1\0").unwrap();
+        assert!(buffer.ext_cstr_list().is_empty());
+    }
+
+    #[test]
+    fn strip_bom_prefix() {
+        assert_eq!((&b"a\0"[..], Some(Endianness::Little)), strip_bom(b"\xFF\xFEa\0"));
+        assert_eq!((&b"\0a"[..], Some(Endianness::Big)), strip_bom(b"\xFE\xFF\0a"));
+        assert_eq!((&b"abc"[..], None), strip_bom(b"\xEF\xBB\xBFabc"));
+        assert_eq!((&b"abc"[..], None), strip_bom(b"abc"));
+        assert_eq!((&b"\xFF"[..], None), strip_bom(b"\xFF"));
+    }
+
+    #[test]
+    fn trailing_fill_count() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(b"NAME    ");
+        assert_eq!(Ok(4), buffer.trailing_fill_count(0..8, b' '));
+        assert_eq!(Ok(0), buffer.trailing_fill_count(0..4, b' '));
+        assert_eq!(Ok(2), buffer.trailing_fill_count(6..8, b' '));
+        assert_eq!(Ok(0), buffer.trailing_fill_count(0..8, 0));
+    }
+
+    #[test]
+    fn trailing_fill_count_invalid_range() {
+        let err = StructBuffer::<[u8; 8]>::zeroed().trailing_fill_count(4..9, 0);
+        assert_eq!(Err(SharedError::OutOfBounds { offset: 4, size: 5, len: 8 }), err);
+    }
+
+    #[test]
+    fn byte_range() {
+        let mut buffer = StructBuffer::<[u8; 4]>::with_value(b"text");
+        assert_eq!(Some((b'e', b'x')), buffer.byte_range());
+
+        buffer.insert_ext(0, &[0xFF, 0]).unwrap();
+        assert_eq!(Some((0, 0xFF)), buffer.byte_range());
+        assert_eq!(None, StructBuffer::<()>::zeroed().byte_range());
+    }
+
+    #[test]
+    fn read_pascal_str() {
+        let buffer = StructBuffer::<[u8; 8]>::with_value(b"\x03abc\x00\x02\xFF\xFE");
+        assert_eq!(Some(&b"abc"[..]), buffer.read_pascal_str(0));
+        assert_eq!(Some("abc"), buffer.read_pascal_str_utf8(0));
+        assert_eq!(Some(&b""[..]), buffer.read_pascal_str(4));
+        assert_eq!(Some(&b"\xFF\xFE"[..]), buffer.read_pascal_str(5));
+        assert_eq!(None, buffer.read_pascal_str_utf8(5));
+        assert_eq!(None, buffer.read_pascal_str(3));
+        assert_eq!(None, buffer.read_pascal_str(8));
+    }
+}
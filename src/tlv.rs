@@ -0,0 +1,68 @@
+use crate::endian::read_u16_le;
+use crate::{SharedError, StructBuffer};
+
+impl<T: Sized + Clone + Copy> StructBuffer<T> {
+    /// Iterates the ext region as a stream of little-endian `(tag: u16, length: u16, value)` entries,
+    /// yielding `(tag, value)` pairs.
+    ///
+    /// Iteration stops when fewer than 4 header bytes remain or a declared length overruns the buffer.
+    pub fn iter_tlv(&self) -> impl Iterator<Item = (u16, &[u8])> {
+        let ext = self.ext_buffer();
+        let mut pos = 0;
+        core::iter::from_fn(move || {
+            let tag = read_u16_le(ext, pos)?;
+            let len = usize::from(read_u16_le(ext, pos + 2)?);
+            let value = ext.get(pos + 4..pos + 4 + len)?;
+            pos += 4 + len;
+            Some((tag, value))
+        })
+    }
+
+    /// Appends a little-endian `(tag, length, value)` entry to the ext region, see [`iter_tlv`](Self::iter_tlv).
+    pub fn push_tlv(&mut self, tag: u16, value: &[u8]) -> Result<(), SharedError> {
+        if value.len() > usize::from(u16::MAX) {
+            return Err(SharedError::ValueTooLarge { value: value.len() as u64, max: u16::MAX.into() });
+        }
+
+        self.buffer.extend_from_slice(&tag.to_le_bytes());
+        self.buffer.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        self.buffer.extend_from_slice(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xstd::Vec;
+
+    #[test]
+    fn iter_tlv() {
+        let mut buffer = StructBuffer::<u8>::zeroed();
+        assert_eq!(0, buffer.iter_tlv().count());
+
+        buffer.insert_ext(0, &[1, 0, 2, 0, b'h', b'i', 2, 0, 0, 0, 3, 0, 9, 0, 1]).unwrap();
+        let entries: Vec<_> = buffer.iter_tlv().collect();
+        assert_eq!(vec![(1, &b"hi"[..]), (2, &b""[..])], entries);
+
+        buffer.remove_ext(10..15).unwrap();
+        buffer.insert_ext(10, &[3, 0]).unwrap();
+        assert_eq!(2, buffer.iter_tlv().count());
+    }
+
+    #[test]
+    fn push_tlv_round_trip() {
+        let mut buffer = StructBuffer::<u16>::with_value(&7);
+        buffer.push_tlv(1, b"name").unwrap();
+        buffer.push_tlv(0xABCD, b"").unwrap();
+        buffer.push_tlv(3, &[0xFF; 300]).unwrap();
+        assert_eq!(7, buffer.copy());
+
+        let entries: Vec<_> = buffer.iter_tlv().collect();
+        assert_eq!(vec![(1, &b"name"[..]), (0xABCD, &b""[..]), (3, &[0xFF; 300][..])], entries);
+
+        let err = buffer.push_tlv(4, &vec![0; 0x1_0000]).unwrap_err();
+        assert_eq!(SharedError::ValueTooLarge { value: 0x1_0000, max: 0xFFFF }, err);
+        assert_eq!(3, buffer.iter_tlv().count());
+    }
+}
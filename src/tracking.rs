@@ -0,0 +1,83 @@
+use crate::endian::read_uint;
+use crate::records::read_value;
+use crate::xstd::{vec, Vec};
+use crate::{Pod, StructBuffer};
+use core::cell::RefCell;
+
+/// A `StructBuffer` wrapper that records which bytes were read through its typed readers.
+///
+/// Useful to check that a parser looks at every byte of a structure.
+pub struct TrackingStructBuffer<T: Sized + Clone + Copy> {
+    inner: StructBuffer<T>,
+    accessed: RefCell<Vec<bool>>,
+}
+
+impl<T: Sized + Clone + Copy> TrackingStructBuffer<T> {
+    pub fn new(inner: StructBuffer<T>) -> Self {
+        let accessed = RefCell::new(vec![false; inner.len()]);
+        Self { inner, accessed }
+    }
+
+    pub fn inner(&self) -> &StructBuffer<T> {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> StructBuffer<T> {
+        self.inner
+    }
+
+    /// Returns `size` bytes at `offset`, or `None` if they do not fit.
+    pub fn read_bytes(&self, offset: usize, size: usize) -> Option<&[u8]> {
+        let bytes = self.inner.buffer().get(offset..offset.checked_add(size)?)?;
+        self.mark(offset, size);
+        Some(bytes)
+    }
+
+    /// Copies a `U` out of the buffer at `offset`, or `None` if it does not fit.
+    pub fn read_value<U: Pod>(&self, offset: usize) -> Option<U> {
+        let value = read_value::<U>(self.inner.buffer(), offset)?;
+        self.mark(offset, core::mem::size_of::<U>());
+        Some(value)
+    }
+
+    /// Reads an unsigned integer of `width` bytes (1 to 8) at `offset`.
+    pub fn read_uint(&self, offset: usize, width: usize, little_endian: bool) -> Option<u64> {
+        let value = read_uint(self.inner.buffer(), offset, width, little_endian)?;
+        self.mark(offset, width);
+        Some(value)
+    }
+
+    /// Returns, for every byte of the buffer, whether it was read since the last reset.
+    pub fn coverage(&self) -> Vec<bool> {
+        self.accessed.borrow().clone()
+    }
+
+    pub fn reset_coverage(&self) {
+        self.accessed.borrow_mut().iter_mut().for_each(|accessed| *accessed = false);
+    }
+
+    fn mark(&self, offset: usize, size: usize) {
+        self.accessed.borrow_mut()[offset..offset + size].iter_mut().for_each(|accessed| *accessed = true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        let tracking = TrackingStructBuffer::new(StructBuffer::<[u8; 6]>::with_value(&[1, 2, 3, 4, 5, 6]));
+        assert_eq!(vec![false; 6], tracking.coverage());
+
+        assert_eq!(Some(0x0201), tracking.read_uint(0, 2, true));
+        assert_eq!(Some(5_u8), tracking.read_value(4));
+        assert_eq!(None, tracking.read_bytes(5, 2));
+        assert_eq!(vec![true, true, false, false, true, false], tracking.coverage());
+
+        tracking.reset_coverage();
+        assert_eq!(Some(&[3, 4][..]), tracking.read_bytes(2, 2));
+        assert_eq!(vec![false, false, true, true, false, false], tracking.coverage());
+        assert_eq!(6, tracking.into_inner().len());
+    }
+}